@@ -1,16 +1,115 @@
 //! Health check builder for composable health checks
 
-use crate::checks::HealthCheck;
-use crate::response::{HealthResponse, CheckResult};
-use crate::routes::HealthRoutes;
+use crate::checks::{selftest_check, CheckSet, FailurePolicy, HealthCheck, Probe, RegisteredCheck};
+use crate::clock::{Clock, SystemClock};
+use crate::config::HealthConfig;
+#[cfg(feature = "axum")]
+use crate::logging::{ProbeLogger, RequestLogging};
+use crate::response::{BuildInfo, DurationUnit, TimestampFormat};
+use crate::response::HealthResponse;
+#[cfg(feature = "axum")]
+use crate::routes::PlainTextBodies;
+use crate::routes::{HealthRoutes, Hysteresis, ReadinessCallback, ReadinessState};
+#[cfg(feature = "axum")]
+use axum::http::{HeaderValue, StatusCode};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Error returned while configuring or building a [`HealthCheckBuilder`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// No readiness checks were registered but at least one is required
+    NoChecks,
+    /// A required environment variable was not set or was empty
+    MissingEnv(String),
+    /// A [`HealthConfig`] named a check that is not registered
+    UnknownCheck(String),
+    /// A check was configured with an unparseable URL
+    InvalidUrl(url::ParseError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::NoChecks => write!(f, "no readiness checks registered"),
+            BuildError::MissingEnv(var) => write!(f, "environment variable {} is not set", var),
+            BuildError::UnknownCheck(name) => write!(f, "config names unknown check {}", name),
+            BuildError::InvalidUrl(e) => write!(f, "invalid check URL: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::NoChecks | BuildError::MissingEnv(_) | BuildError::UnknownCheck(_) => None,
+            BuildError::InvalidUrl(e) => Some(e),
+        }
+    }
+}
+
+impl From<url::ParseError> for BuildError {
+    fn from(e: url::ParseError) -> Self {
+        BuildError::InvalidUrl(e)
+    }
+}
+
+/// Create a [`HealthCheckBuilder`] named and versioned after the calling crate
+///
+/// Uses `CARGO_PKG_NAME` and `CARGO_PKG_VERSION` when compiling the calling
+/// crate, so the reported version can't drift from `Cargo.toml`.
+///
+/// ```rust
+/// let health = pleme_health::from_cargo!().build();
+/// ```
+#[macro_export]
+macro_rules! from_cargo {
+    () => {
+        $crate::HealthCheckBuilder::new(
+            ::std::env!("CARGO_PKG_NAME"),
+            ::std::env!("CARGO_PKG_VERSION"),
+        )
+    };
+}
 
 /// Builder for composable health checks
 pub struct HealthCheckBuilder {
     service_name: String,
     version: Option<String>,
-    checks: HashMap<String, HealthCheck>,
+    environment: Option<String>,
+    instance_id: Option<String>,
+    build_info: Option<BuildInfo>,
+    checks: IndexMap<String, RegisteredCheck>,
+    timestamp_format: TimestampFormat,
+    include_summary: bool,
+    include_timing: bool,
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "axum")]
+    compression_threshold: Option<u16>,
+    #[cfg(feature = "axum")]
+    cors_origins: Option<Vec<HeaderValue>>,
+    duration_unit: DurationUnit,
+    fail_fast: bool,
+    #[cfg(feature = "axum")]
+    request_logging: Option<RequestLogging>,
+    require_checks: bool,
+    #[cfg(feature = "axum")]
+    maintenance_token: Option<String>,
+    #[cfg(feature = "axum")]
+    mixed_status: Option<StatusCode>,
+    default_timeout: Option<Duration>,
+    warmup: Option<Duration>,
+    optional_failure_threshold: Option<usize>,
+    readiness_callback: Option<ReadinessCallback>,
+    hysteresis: Option<Hysteresis>,
+    #[cfg(feature = "axum")]
+    plain_text: Option<PlainTextBodies>,
 }
 
 impl HealthCheckBuilder {
@@ -19,31 +118,521 @@ impl HealthCheckBuilder {
         Self {
             service_name: service_name.into(),
             version: Some(version.into()),
-            checks: HashMap::new(),
+            environment: None,
+            instance_id: None,
+            build_info: None,
+            checks: IndexMap::new(),
+            timestamp_format: TimestampFormat::default(),
+            include_summary: false,
+            include_timing: false,
+            clock: Arc::new(SystemClock),
+            #[cfg(feature = "axum")]
+            compression_threshold: None,
+            #[cfg(feature = "axum")]
+            cors_origins: None,
+            duration_unit: DurationUnit::default(),
+            fail_fast: false,
+            #[cfg(feature = "axum")]
+            request_logging: None,
+            require_checks: false,
+            #[cfg(feature = "axum")]
+            maintenance_token: None,
+            #[cfg(feature = "axum")]
+            mixed_status: None,
+            default_timeout: None,
+            warmup: None,
+            optional_failure_threshold: None,
+            readiness_callback: None,
+            hysteresis: None,
+            #[cfg(feature = "axum")]
+            plain_text: None,
         }
     }
 
     /// Create a builder without version
     pub fn without_version(service_name: impl Into<String>) -> Self {
         Self {
-            service_name: service_name.into(),
             version: None,
-            checks: HashMap::new(),
+            ..Self::new(service_name, String::new())
         }
     }
 
+    /// Create a builder from service name and version environment variables
+    ///
+    /// e.g. `from_env("SERVICE_NAME", "SERVICE_VERSION")`. The name variable
+    /// is required; if the version variable is unset the builder is created
+    /// [`without_version`](Self::without_version). See
+    /// [`from_cargo!`](crate::from_cargo) to use the crate's own metadata.
+    pub fn from_env(name_var: &str, version_var: &str) -> Result<Self, BuildError> {
+        let read = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+
+        let service_name =
+            read(name_var).ok_or_else(|| BuildError::MissingEnv(name_var.to_string()))?;
+
+        Ok(match read(version_var) {
+            Some(version) => Self::new(service_name, version),
+            None => Self::without_version(service_name),
+        })
+    }
+
+    /// Set the deployment environment reported by both endpoints
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Set the instance identity reported by both endpoints
+    ///
+    /// Defaults to the hostname (the pod name on Kubernetes) when available.
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Set build metadata reported by both endpoints
+    ///
+    /// See [`build_info!`](crate::build_info) to capture it at compile time.
+    pub fn with_build_info(mut self, build_info: BuildInfo) -> Self {
+        self.build_info = Some(build_info);
+        self
+    }
+
     /// Add a health check
     pub fn add_check(mut self, name: impl Into<String>, check: HealthCheck) -> Self {
-        self.checks.insert(name.into(), check);
+        self.checks.insert(name.into(), RegisteredCheck::new(check));
+        self
+    }
+
+    /// Add a health check that tolerates transient failures
+    ///
+    /// The check is only reported unhealthy once it has failed
+    /// `policy.consecutive_failures` evaluations in a row; failures before
+    /// that are reported as degraded. A healthy result resets the streak.
+    pub fn add_check_with_policy(
+        mut self,
+        name: impl Into<String>,
+        check: HealthCheck,
+        policy: FailurePolicy,
+    ) -> Self {
+        let mut registered = RegisteredCheck::new(check);
+        registered.failure_policy = policy;
+        self.checks.insert(name.into(), registered);
+        self
+    }
+
+    /// Add a health check with its own timeout
+    ///
+    /// Overrides the [`default_check_timeout`](Self::default_check_timeout)
+    /// for this check.
+    pub fn add_check_with_timeout(
+        mut self,
+        name: impl Into<String>,
+        check: HealthCheck,
+        timeout: Duration,
+    ) -> Self {
+        let mut registered = RegisteredCheck::new(check);
+        registered.timeout = Some(timeout);
+        self.checks.insert(name.into(), registered);
+        self
+    }
+
+    /// Add a health check whose failure degrades rather than fails readiness
+    ///
+    /// Use for non-critical dependencies the service can run without.
+    pub fn add_optional_check(mut self, name: impl Into<String>, check: HealthCheck) -> Self {
+        let mut registered = RegisteredCheck::new(check);
+        registered.critical = false;
+        self.checks.insert(name.into(), registered);
+        self
+    }
+
+    /// Tolerate up to `n` failing optional checks before degrading
+    ///
+    /// By default any failing optional check degrades readiness. With this
+    /// policy, failures are reported per check but only degrade the overall
+    /// status once more than `n` optional checks fail in the same
+    /// evaluation, signalling a systemic problem rather than one flaky
+    /// integration. Any optional result other than healthy (degraded,
+    /// unknown or unhealthy) counts as a failure.
+    pub fn degrade_when_optional_failures_exceed(mut self, n: usize) -> Self {
+        self.optional_failure_threshold = Some(n);
+        self
+    }
+
+    /// Add a health check that runs on the liveness probe
+    ///
+    /// When it fails, `/health` returns 503 so the orchestrator restarts the
+    /// process. Use for conditions a restart fixes, such as a wedged task.
+    pub fn add_liveness_check(self, name: impl Into<String>, check: HealthCheck) -> Self {
+        self.add_check_for(name, check, Probe::Liveness)
+    }
+
+    /// Run a minimal self-test on the liveness probe
+    ///
+    /// Registers a `_selftest` liveness check confirming the runtime can
+    /// still schedule a task, so a process that is wedged but still serving
+    /// HTTP gets restarted. It takes microseconds when healthy. The leading
+    /// underscore keeps it from replacing an application check named
+    /// `selftest`.
+    pub fn with_liveness_selftest(self) -> Self {
+        self.add_liveness_check("_selftest", selftest_check())
+    }
+
+    /// Add a health check that runs on the given probes
+    pub fn add_check_for(mut self, name: impl Into<String>, check: HealthCheck, probe: Probe) -> Self {
+        let mut registered = RegisteredCheck::new(check);
+        registered.probe = probe;
+        self.checks.insert(name.into(), registered);
+        self
+    }
+
+    /// Add a health check that only runs while `gate` is `true`
+    ///
+    /// The gate is read on every readiness probe, so flipping it at runtime
+    /// takes effect on the next probe. A disabled check is omitted from the
+    /// response entirely.
+    pub fn add_toggleable_check(
+        mut self,
+        name: impl Into<String>,
+        check: HealthCheck,
+        gate: Arc<AtomicBool>,
+    ) -> Self {
+        let mut registered = RegisteredCheck::new(check);
+        registered.gate = Some(gate);
+        self.checks.insert(name.into(), registered);
+        self
+    }
+
+    /// Apply a timeout to every check without one of its own
+    ///
+    /// Checks that exceed it report unhealthy, as with
+    /// [`with_timeout`](crate::checks::with_timeout). Checks registered with
+    /// [`add_check_with_timeout`](Self::add_check_with_timeout) keep their
+    /// own timeout.
+    pub fn default_check_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Enable, disable and tune registered checks from a [`HealthConfig`]
+    ///
+    /// Call after registering the checks. Disabled checks are removed;
+    /// configured timeouts and criticality override the registration, and a
+    /// configured default timeout replaces
+    /// [`default_check_timeout`](Self::default_check_timeout). Fails if the
+    /// config names a check that is not registered, so typos surface at
+    /// startup.
+    pub fn apply_config(mut self, config: &HealthConfig) -> Result<Self, BuildError> {
+        if let Some(name) = config.checks.keys().find(|name| !self.checks.contains_key(*name)) {
+            return Err(BuildError::UnknownCheck(name.clone()));
+        }
+
+        if let Some(timeout) = config.default_timeout() {
+            self.default_timeout = Some(timeout);
+        }
+
+        for (name, check_config) in &config.checks {
+            if !check_config.enabled {
+                self.checks.shift_remove(name);
+                continue;
+            }
+
+            if let Some(registered) = self.checks.get_mut(name) {
+                if let Some(timeout) = check_config.timeout() {
+                    registered.timeout = Some(timeout);
+                }
+                if let Some(critical) = check_config.critical {
+                    registered.critical = critical;
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Fail readiness for `period` after the routes are built
+    ///
+    /// During warmup `/ready` returns 503 with `"reason": "warming up"`
+    /// without running any checks, so a new instance can't take traffic while
+    /// its pools are still connecting. Unlike a startup probe this is purely
+    /// time-based.
+    pub fn with_warmup(mut self, period: Duration) -> Self {
+        self.warmup = Some(period);
+        self
+    }
+
+    /// Call `callback` whenever aggregate readiness flips
+    ///
+    /// Receives the previous and new readiness (`true` when `/ready` would
+    /// pass) and the time of the evaluation that caused the flip. Only
+    /// transitions are reported, not every evaluation; with
+    /// [`spawn_background_checks`](HealthRoutes::spawn_background_checks)
+    /// this yields unavailability windows without external polling.
+    pub fn on_readiness_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(bool, bool, DateTime<Utc>) + Send + Sync + 'static,
+    {
+        self.readiness_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Debounce readiness flips to avoid endpoint churn
+    ///
+    /// Once ready, failing evaluations keep reporting ready (as degraded)
+    /// until they have persisted for `unready_after`. Once unready, passing
+    /// evaluations keep reporting unready (as unknown) until they have
+    /// persisted for `ready_after`, including right after startup. Held
+    /// responses explain themselves in `reason`. Maintenance and warmup take
+    /// effect immediately.
+    pub fn with_readiness_hysteresis(
+        mut self,
+        unready_after: Duration,
+        ready_after: Duration,
+    ) -> Self {
+        self.hysteresis = Some(Hysteresis {
+            unready_after,
+            ready_after,
+        });
+        self
+    }
+
+    /// Answer `/ready` with a plain-text token instead of JSON
+    ///
+    /// For load balancers that match the body rather than the status code,
+    /// e.g. `with_plain_text_readiness("READY", "NOT READY")`. Status codes
+    /// are unchanged; the `detail` query parameter is ignored.
+    #[cfg(feature = "axum")]
+    pub fn with_plain_text_readiness(
+        mut self,
+        ready: impl Into<String>,
+        not_ready: impl Into<String>,
+    ) -> Self {
+        self.plain_text = Some(PlainTextBodies {
+            ready: ready.into(),
+            not_ready: not_ready.into(),
+        });
+        self
+    }
+
+    /// Set the serialization format of the response `timestamp`
+    ///
+    /// Defaults to [`TimestampFormat::Rfc3339`].
+    pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    /// Allow browsers on `origins` to call the health endpoints
+    ///
+    /// Adds CORS headers for `GET` requests from the listed origins and
+    /// answers `OPTIONS` preflights. Server-to-server probes are unaffected.
+    #[cfg(feature = "axum")]
+    pub fn with_cors<I>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderValue>,
+    {
+        self.cors_origins = Some(origins.into_iter().collect());
+        self
+    }
+
+    /// Set the unit of per-check durations in responses
+    ///
+    /// Defaults to [`DurationUnit::Milliseconds`] (`duration_ms`). Checks
+    /// that record a full-precision duration are converted without first
+    /// rounding to milliseconds.
+    pub fn with_duration_unit(mut self, unit: DurationUnit) -> Self {
+        self.duration_unit = unit;
+        self
+    }
+
+    /// Stop evaluating as soon as a critical check is unhealthy
+    ///
+    /// Trades a complete diagnostic for lower probe latency and load: the
+    /// remaining checks are not run and are reported as
+    /// `unknown("not evaluated: fail-fast")`.
+    pub fn with_fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
+
+    /// Emit one structured `tracing` event per readiness probe
+    ///
+    /// Logs overall status, per-check status and duration, and the client
+    /// address (when the app is served with `into_make_service_with_connect_info`).
+    /// Only probes that change the overall status are logged; see
+    /// [`with_request_logging_policy`](Self::with_request_logging_policy) to
+    /// sample or log everything.
+    #[cfg(feature = "axum")]
+    pub fn with_request_logging(self) -> Self {
+        self.with_request_logging_policy(RequestLogging::OnChange)
+    }
+
+    /// Emit structured `tracing` events for readiness probes per `policy`
+    #[cfg(feature = "axum")]
+    pub fn with_request_logging_policy(mut self, policy: RequestLogging) -> Self {
+        self.request_logging = Some(policy);
+        self
+    }
+
+    /// Treat an empty readiness check set as a configuration error
+    ///
+    /// [`try_build`](Self::try_build) fails when no readiness checks are
+    /// registered, and a readiness probe that ends up evaluating no checks
+    /// (e.g. all toggled off) reports `unknown` with a 503 instead of
+    /// claiming the service is ready.
+    pub fn require_at_least_one_check(mut self) -> Self {
+        self.require_checks = true;
         self
     }
 
+    /// Mount `POST /maintenance` to toggle maintenance mode over HTTP
+    ///
+    /// Requests must send `Authorization: Bearer <token>` and a JSON body
+    /// `{"enabled": true|false}`. See
+    /// [`HealthRoutes::set_maintenance`] for the in-process API.
+    #[cfg(feature = "axum")]
+    pub fn with_maintenance_endpoint(mut self, token: impl Into<String>) -> Self {
+        self.maintenance_token = Some(token.into());
+        self
+    }
+
+    /// Return `207 Multi-Status` from `/ready` when check results are mixed
+    ///
+    /// See [`with_mixed_status_code`](Self::with_mixed_status_code).
+    #[cfg(feature = "axum")]
+    pub fn with_multi_status(self) -> Self {
+        self.with_mixed_status_code(StatusCode::MULTI_STATUS)
+    }
+
+    /// Return `code` from `/ready` when some checks are healthy and others are not
+    ///
+    /// Intended for clients that route on partial health; the body details
+    /// each check as usual. Takes precedence over the 200/503 mapping, so
+    /// leave it off for Kubernetes probes.
+    #[cfg(feature = "axum")]
+    pub fn with_mixed_status_code(mut self, code: StatusCode) -> Self {
+        self.mixed_status = Some(code);
+        self
+    }
+
+    /// Include a `summary` of check counts by status in readiness responses
+    pub fn with_summary(mut self) -> Self {
+        self.include_summary = true;
+        self
+    }
+
+    /// Include `total_duration_ms` and `slowest_check` in readiness responses
+    pub fn with_timing(mut self) -> Self {
+        self.include_timing = true;
+        self
+    }
+
+    /// Use `clock` for response timestamps and evaluation timing
+    ///
+    /// Defaults to [`SystemClock`]. Built-in checks and combinators measure
+    /// their durations on it too, as does the routes' timing of checks that
+    /// report none; custom checks that time themselves are unaffected.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Compress responses larger than `min_bytes` with gzip or deflate
+    ///
+    /// Only applies when the client sends a matching `Accept-Encoding`;
+    /// probes that don't advertise encoding support get the uncompressed
+    /// body. The status stream is never compressed.
+    #[cfg(feature = "axum")]
+    pub fn with_compression(mut self, min_bytes: u16) -> Self {
+        self.compression_threshold = Some(min_bytes);
+        self
+    }
+
+    /// Build the health check system, validating the configuration
+    pub fn try_build(self) -> Result<HealthRoutes, BuildError> {
+        let has_readiness_check = self
+            .checks
+            .values()
+            .any(|registered| registered.probe.readiness());
+
+        if self.require_checks && !has_readiness_check {
+            return Err(BuildError::NoChecks);
+        }
+
+        Ok(self.build())
+    }
+
     /// Build the health check system
     pub fn build(self) -> HealthRoutes {
+        let (updates, _) = broadcast::channel(16);
+
+        let warmup_until = self.warmup.map(|period| self.clock.instant() + period);
+        let default_timeout = self.default_timeout;
+        let checks = self
+            .checks
+            .into_iter()
+            .map(|(name, registered)| {
+                (name, Arc::new(registered.with_timeout_applied(default_timeout)))
+            })
+            .collect();
+        let checks = CheckSet::new(checks);
+
         HealthRoutes {
             service_name: Arc::new(self.service_name),
             version: self.version.map(Arc::new),
-            checks: Arc::new(self.checks),
+            environment: self.environment.map(Arc::new),
+            instance_id: self.instance_id.or_else(hostname).map(Arc::new),
+            build_info: self.build_info.map(Arc::new),
+            checks: Arc::new(checks),
+            timestamp_format: self.timestamp_format,
+            include_summary: self.include_summary,
+            include_timing: self.include_timing,
+            clock: self.clock,
+            #[cfg(feature = "axum")]
+            compression_threshold: self.compression_threshold,
+            #[cfg(feature = "axum")]
+            cors_origins: self.cors_origins.map(Arc::new),
+            duration_unit: self.duration_unit,
+            fail_fast: self.fail_fast,
+            require_checks: self.require_checks,
+            check_state: Arc::new(Mutex::new(HashMap::new())),
+            maintenance: Arc::new(AtomicBool::new(false)),
+            inflight: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "axum")]
+            maintenance_token: self.maintenance_token.map(Arc::new),
+            #[cfg(feature = "axum")]
+            mixed_status: self.mixed_status,
+            warmup_until,
+            optional_failure_threshold: self.optional_failure_threshold,
+            readiness_callback: self.readiness_callback,
+            default_timeout,
+            hysteresis: self.hysteresis,
+            readiness_state: Arc::new(Mutex::new(ReadinessState::default())),
+            #[cfg(feature = "axum")]
+            plain_text: self.plain_text.map(Arc::new),
+            #[cfg(feature = "prometheus")]
+            metrics: crate::metrics::CheckMetrics::new(),
+            #[cfg(feature = "axum")]
+            probe_logger: self.request_logging.map(|policy| Arc::new(ProbeLogger::new(policy))),
+            updates,
+            latest: Arc::new(Mutex::new(None)),
         }
     }
+
+    /// Build and run every readiness check once
+    ///
+    /// For CLIs, batch jobs and startup gates that want a [`HealthResponse`]
+    /// without serving HTTP. Works without the `axum` feature.
+    pub async fn evaluate(self) -> HealthResponse {
+        self.build().check().await
+    }
+}
+
+/// Best-effort hostname lookup without extra dependencies
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
 }
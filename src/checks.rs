@@ -1,32 +1,304 @@
 //! Built-in health checks for common dependencies
+//!
+//! # Blocking work
+//!
+//! Checks run inline on the task serving the probe. All built-in checks are
+//! async or only touch in-memory state and procfs ([`memory_check`],
+//! [`heartbeat_check`]), so they are safe to run inline. Custom checks that
+//! call blocking code (synchronous drivers, FFI, filesystem scans) should be
+//! wrapped with [`blocking_check`] so they run on Tokio's blocking thread
+//! pool instead of stalling an async worker.
+//!
+//! # Application state
+//!
+//! Checks capture what they need when constructed. For state that only
+//! exists once the application has started, such as a connection registry
+//! or feature-flag client created after the router is built, register a
+//! [`state_check`] over a shared `OnceLock` and fill it in later:
+//!
+//! ```rust,no_run
+//! use pleme_health::checks::state_check;
+//! use pleme_health::{CheckResult, HealthCheckBuilder};
+//! use std::sync::{Arc, OnceLock};
+//!
+//! #[derive(Clone)]
+//! struct AppState {
+//!     flags_connected: bool,
+//! }
+//!
+//! let state = Arc::new(OnceLock::new());
+//! let health = HealthCheckBuilder::new("my-service", "1.0.0")
+//!     .add_check("flags", state_check(state.clone(), |app: AppState| async move {
+//!         if app.flags_connected {
+//!             CheckResult::healthy()
+//!         } else {
+//!             CheckResult::unhealthy("feature-flag client disconnected")
+//!         }
+//!     }))
+//!     .build();
+//!
+//! // Later, once the state exists
+//! let _ = state.set(AppState { flags_connected: true });
+//! ```
 
-use crate::response::CheckResult;
+use crate::clock::Clock;
+use crate::response::{CheckResult, CheckStatus};
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use futures::FutureExt;
+use indexmap::IndexMap;
+use std::any::Any;
+use std::fmt;
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
 
 /// Type alias for async health check functions
 pub type HealthCheck = Box<dyn Fn() -> Pin<Box<dyn Future<Output = CheckResult> + Send>> + Send + Sync>;
 
+/// Probe endpoints a check runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Probe {
+    /// `/health` only: failure means the process should be restarted
+    Liveness,
+    /// `/ready` only: failure means traffic should stop being routed here
+    #[default]
+    Readiness,
+    /// Both `/health` and `/ready`
+    Both,
+}
+
+impl Probe {
+    /// Whether the check runs on the liveness probe
+    pub fn liveness(self) -> bool {
+        matches!(self, Probe::Liveness | Probe::Both)
+    }
+
+    /// Whether the check runs on the readiness probe
+    pub fn readiness(self) -> bool {
+        matches!(self, Probe::Readiness | Probe::Both)
+    }
+}
+
+/// How many failures in a row a check tolerates before reporting unhealthy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailurePolicy {
+    /// Consecutive unhealthy results needed before the check is reported
+    /// unhealthy; earlier failures are reported as degraded
+    pub consecutive_failures: u32,
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        Self {
+            consecutive_failures: 1,
+        }
+    }
+}
+
+/// A health check registered with the builder, along with its options
+pub(crate) struct RegisteredCheck {
+    pub(crate) check: HealthCheck,
+    pub(crate) gate: Option<Arc<AtomicBool>>,
+    pub(crate) probe: Probe,
+    pub(crate) critical: bool,
+    pub(crate) failure_policy: FailurePolicy,
+    pub(crate) timeout: Option<Duration>,
+}
+
+/// State tracked for a check across evaluations
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CheckState {
+    pub(crate) consecutive_failures: u32,
+    pub(crate) last_failure_at: Option<DateTime<Utc>>,
+    /// Id of the latest execution applied, so joined callers count it once
+    pub(crate) last_execution: u64,
+}
+
+impl RegisteredCheck {
+    pub(crate) fn new(check: HealthCheck) -> Self {
+        Self {
+            check,
+            gate: None,
+            probe: Probe::default(),
+            critical: true,
+            failure_policy: FailurePolicy::default(),
+            timeout: None,
+        }
+    }
+
+    /// Whether the check should run on this evaluation
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.gate
+            .as_ref()
+            .is_none_or(|gate| gate.load(Ordering::Relaxed))
+    }
+
+    /// Wrap the check in its own timeout, or `default_timeout` if it has none
+    pub(crate) fn with_timeout_applied(mut self, default_timeout: Option<Duration>) -> Self {
+        if let Some(timeout) = self.timeout.or(default_timeout) {
+            self.check = with_timeout(self.check, timeout);
+        }
+        self
+    }
+}
+
+/// Registered checks keyed by name, in registration order
+pub(crate) type CheckMap = IndexMap<String, Arc<RegisteredCheck>>;
+
+/// The registered checks, swappable at runtime
+pub(crate) struct CheckSet(ArcSwap<CheckMap>);
+
+impl CheckSet {
+    pub(crate) fn new(checks: CheckMap) -> Self {
+        Self(ArcSwap::from_pointee(checks))
+    }
+
+    /// Snapshot of the current checks
+    pub(crate) fn load(&self) -> Arc<CheckMap> {
+        self.0.load_full()
+    }
+
+    /// Atomically replace the checks with `f` applied to the current set
+    pub(crate) fn update(&self, f: impl Fn(&CheckMap) -> CheckMap) {
+        self.0.rcu(|current| f(current));
+    }
+}
+
+/// Run a health check, reporting a panic as an unhealthy result
+///
+/// Catches panics both while creating the check future and while polling
+/// it, so a buggy check cannot take down the probe.
+pub(crate) async fn run_check(check: &HealthCheck) -> CheckResult {
+    let future = match std::panic::catch_unwind(AssertUnwindSafe(check)) {
+        Ok(future) => future,
+        Err(panic) => return panicked(panic),
+    };
+
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => panicked(panic),
+    }
+}
+
+tokio::task_local! {
+    /// Clock of the routes evaluating the current check
+    static CLOCK: Arc<dyn Clock>;
+}
+
+/// Run a health check with `clock` measuring the built-in checks' durations
+pub(crate) async fn run_check_with_clock(
+    check: &HealthCheck,
+    clock: Arc<dyn Clock>,
+) -> CheckResult {
+    CLOCK.scope(clock, run_check(check)).await
+}
+
+/// Current instant on the evaluating routes' clock, or the system clock
+fn clock_now() -> Instant {
+    CLOCK.try_with(|clock| clock.instant()).unwrap_or_else(|_| Instant::now())
+}
+
+/// Time since `start` on the evaluating routes' clock
+fn elapsed_since(start: Instant) -> Duration {
+    clock_now().saturating_duration_since(start)
+}
+
+/// Convert a caught panic payload into an unhealthy result
+fn panicked(panic: Box<dyn Any + Send>) -> CheckResult {
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned());
+
+    match message {
+        Some(message) => CheckResult::unhealthy(format!("check panicked: {}", message)),
+        None => CheckResult::unhealthy("check panicked"),
+    }
+    .with_code("check.panic")
+}
+
+/// How long the Postgres check waits for a pooled connection
+const POOL_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Create a PostgreSQL health check
 ///
-/// Executes `SELECT 1` to verify database connectivity
+/// Executes `SELECT 1` to verify database connectivity. Pool statistics are
+/// sampled first; when the pool is saturated (at its maximum size with no
+/// idle connections) the check reports degraded without waiting for a
+/// connection, pointing at pool exhaustion rather than the database. A
+/// connection that can't be acquired within a second is reported the same
+/// way, instead of blocking for the pool's acquire timeout.
+///
+/// Acquiring the connection and running the query are timed separately and
+/// reported as `acquire_ms` and `query_ms`; slow acquisition points at the
+/// pool, a slow query at the database. The duration is their sum.
 pub fn postgres_check(pool: sqlx::PgPool) -> HealthCheck {
     Box::new(move || {
         let pool = pool.clone();
         Box::pin(async move {
-            let start = Instant::now();
+            let size = pool.size();
+            let idle = pool.num_idle() as u32;
+            let max_connections = pool.options().get_max_connections();
+            let with_pool_stats = |result: CheckResult| {
+                result
+                    .with_detail("pool_size", size)
+                    .with_detail("pool_idle", idle)
+                    .with_detail("pool_max", max_connections)
+            };
 
-            match sqlx::query("SELECT 1")
-                .fetch_one(&pool)
-                .await
-            {
-                Ok(_) => {
-                    let duration = start.elapsed().as_millis() as u64;
-                    CheckResult::healthy()
-                        .with_duration(duration)
+            if idle == 0 && size >= max_connections {
+                return with_pool_stats(
+                    CheckResult::degraded(format!(
+                        "Connection pool saturated: {} of {} connections in use",
+                        size, max_connections
+                    ))
+                    .with_code("db.pool_saturated"),
+                );
+            }
+
+            let start = clock_now();
+            let acquired = tokio::time::timeout(POOL_ACQUIRE_TIMEOUT, pool.acquire()).await;
+            let acquire = elapsed_since(start);
+
+            let mut query = None;
+            let result = match acquired {
+                Ok(Ok(mut connection)) => {
+                    let start = clock_now();
+                    let outcome = sqlx::query("SELECT 1").fetch_one(&mut *connection).await;
+                    let elapsed = elapsed_since(start);
+                    query = Some(elapsed);
+
+                    match outcome {
+                        Ok(_) => {
+                            CheckResult::healthy()
+                                .with_elapsed(acquire + elapsed)
+                        }
+                        Err(e) => CheckResult::unhealthy(format!("Database query failed: {}", e))
+                            .with_code("db.query")
+                            .with_causes(&e),
+                    }
                 }
-                Err(e) => CheckResult::unhealthy(format!("Database connection failed: {}", e)),
+                Ok(Err(e)) => CheckResult::unhealthy(format!("Database connection failed: {}", e))
+                    .with_code("db.acquire")
+                    .with_causes(&e),
+                Err(_) => CheckResult::degraded(format!(
+                    "Connection pool saturated: no connection available within {}ms",
+                    POOL_ACQUIRE_TIMEOUT.as_millis()
+                ))
+                .with_elapsed(acquire)
+                .with_code("db.pool_saturated"),
+            };
+
+            let result =
+                with_pool_stats(result).with_detail("acquire_ms", acquire.as_millis() as u64);
+
+            match query {
+                Some(query) => result.with_detail("query_ms", query.as_millis() as u64),
+                None => result,
             }
         })
     })
@@ -34,68 +306,878 @@ pub fn postgres_check(pool: sqlx::PgPool) -> HealthCheck {
 
 /// Create a Redis health check
 ///
-/// Executes `PING` to verify Redis connectivity
+/// Executes `PING` to verify Redis connectivity. `rediss://` URLs need the
+/// `redis-tls` feature; see [`redis_check_with_config`] for explicit TLS and
+/// AUTH settings with distinct handshake and authentication failures.
 pub fn redis_check(redis_url: String) -> HealthCheck {
     Box::new(move || {
         let redis_url = redis_url.clone();
         Box::pin(async move {
-            let start = Instant::now();
+            let start = clock_now();
 
             match redis::Client::open(redis_url.as_str()) {
                 Ok(client) => {
                     match client.get_multiplexed_async_connection().await {
-                        Ok(mut con) => {
-                            use redis::AsyncCommands;
-                            // Use SET/GET instead of PING for health check
-                            match con.get::<&str, Option<String>>("__health_check__").await {
-                                Ok(_) => {
-                                    let duration = start.elapsed().as_millis() as u64;
-                                    CheckResult::healthy()
-                                        .with_duration(duration)
-                                }
-                                Err(e) => CheckResult::unhealthy(format!("Redis check failed: {}", e)),
-                            }
-                        }
-                        Err(e) => CheckResult::unhealthy(format!("Redis connection failed: {}", e)),
+                        Ok(mut con) => redis_round_trip(&mut con, start).await,
+                        Err(e) => CheckResult::unhealthy(format!("Redis connection failed: {}", e))
+                            .with_code("redis.connect")
+                            .with_causes(&e),
                     }
                 }
-                Err(e) => CheckResult::unhealthy(format!("Redis client creation failed: {}", e)),
+                Err(e) => CheckResult::unhealthy(format!("Redis client creation failed: {}", e))
+                    .with_code("redis.config")
+                    .with_causes(&e),
             }
         })
     })
 }
 
+/// Connection settings for [`redis_check_with_config`]
+#[derive(Clone)]
+pub struct RedisConfig {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    tls: bool,
+}
+
+impl RedisConfig {
+    /// Connect to `url` (`redis://host:port/db`) without TLS or AUTH
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            username: None,
+            password: None,
+            tls: false,
+        }
+    }
+
+    /// Authenticate with `password`, overriding any password in the URL
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Authenticate as the ACL user `username`
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Require TLS, upgrading a `redis://` URL
+    ///
+    /// Needs the `redis-tls` feature. Without this, a `rediss://` URL is
+    /// rejected rather than silently enabling TLS.
+    pub fn with_tls(mut self) -> Self {
+        self.tls = true;
+        self
+    }
+
+    /// Resolve the URL and explicit settings into connection info
+    fn connection_info(&self) -> Result<redis::ConnectionInfo, String> {
+        use redis::{ConnectionAddr, IntoConnectionInfo};
+
+        if self.url.starts_with("rediss://") && !self.tls {
+            return Err("Redis URL uses rediss:// but TLS is not enabled".to_string());
+        }
+        if self.tls && !cfg!(feature = "redis-tls") {
+            return Err("Redis TLS requires the redis-tls feature".to_string());
+        }
+
+        let mut info = self
+            .url
+            .as_str()
+            .into_connection_info()
+            .map_err(|e| format!("Invalid Redis URL: {}", e))?;
+
+        info.addr = match (info.addr, self.tls) {
+            (ConnectionAddr::Tcp(host, port), true) => ConnectionAddr::TcpTls {
+                host,
+                port,
+                insecure: false,
+                tls_params: None,
+            },
+            (ConnectionAddr::Unix(_), true) => {
+                return Err("Redis TLS is not supported over a Unix socket".to_string())
+            }
+            (addr, _) => addr,
+        };
+
+        if let Some(username) = &self.username {
+            info.redis.username = Some(username.clone());
+        }
+        if let Some(password) = &self.password {
+            info.redis.password = Some(password.clone());
+        }
+
+        Ok(info)
+    }
+}
+
+impl fmt::Debug for RedisConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisConfig")
+            .field("url", &redact_url(&self.url))
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("tls", &self.tls)
+            .finish()
+    }
+}
+
+/// The URL with any password replaced, or a placeholder if it does not parse
+fn redact_url(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(mut parsed) => {
+            if parsed.password().is_some() {
+                // Only fails for URLs that cannot carry credentials at all
+                let _ = parsed.set_password(Some("redacted"));
+            }
+            parsed.to_string()
+        }
+        Err(_) => "<redacted>".to_string(),
+    }
+}
+
+/// Create a Redis health check from explicit connection settings
+///
+/// Connects in stages so failures are actionable: an unreachable host
+/// reports `redis.connect`, a failed TLS handshake `redis.tls`, rejected
+/// credentials `redis.auth`, and an invalid or inconsistent configuration
+/// `redis.config`. Then performs the same round-trip as [`redis_check`].
+pub fn redis_check_with_config(config: RedisConfig) -> HealthCheck {
+    Box::new(move || {
+        let config = config.clone();
+        Box::pin(async move {
+            let start = clock_now();
+
+            let info = match config.connection_info() {
+                Ok(info) => info,
+                Err(message) => return CheckResult::unhealthy(message).with_code("redis.config"),
+            };
+
+            // Reach the server over plain TCP first so that a later I/O
+            // failure on a TLS connection can be attributed to the handshake
+            if let redis::ConnectionAddr::Tcp(host, port)
+            | redis::ConnectionAddr::TcpTls { host, port, .. } = &info.addr
+            {
+                if let Err(e) = tokio::net::TcpStream::connect((host.as_str(), *port)).await {
+                    return CheckResult::unhealthy(format!(
+                        "Redis unreachable at {}:{}: {}",
+                        host, port, e
+                    ))
+                    .with_code("redis.connect")
+                    .with_causes(&e);
+                }
+            }
+
+            let client = match redis::Client::open(info) {
+                Ok(client) => client,
+                Err(e) => {
+                    return CheckResult::unhealthy(format!("Redis client creation failed: {}", e))
+                        .with_code("redis.config")
+                        .with_causes(&e)
+                }
+            };
+
+            match client.get_multiplexed_async_connection().await {
+                Ok(mut con) => redis_round_trip(&mut con, start).await,
+                Err(e) => redis_connect_failure(&e, config.tls),
+            }
+        })
+    })
+}
+
+/// Classify a Redis connection error as an auth, TLS or connection failure
+fn redis_connect_failure(e: &redis::RedisError, tls: bool) -> CheckResult {
+    let auth = e.kind() == redis::ErrorKind::AuthenticationFailed
+        || matches!(e.code(), Some("WRONGPASS" | "NOAUTH"));
+
+    let (message, code) = if auth {
+        ("Redis authentication failed", "redis.auth")
+    } else if tls && e.is_io_error() {
+        ("Redis TLS handshake failed", "redis.tls")
+    } else {
+        ("Redis connection failed", "redis.connect")
+    };
+
+    CheckResult::unhealthy(format!("{}: {}", message, e))
+        .with_code(code)
+        .with_causes(e)
+}
+
+/// Create a Redis Cluster health check
+///
+/// Connects through `redis::cluster::ClusterClient` using the given seed
+/// nodes and performs the same round-trip as [`redis_check`].
+pub fn redis_cluster_check(nodes: Vec<String>) -> HealthCheck {
+    Box::new(move || {
+        let nodes = nodes.clone();
+        Box::pin(async move {
+            let start = clock_now();
+
+            match redis::cluster::ClusterClient::new(nodes) {
+                Ok(client) => match client.get_async_connection().await {
+                    Ok(mut con) => redis_round_trip(&mut con, start).await,
+                    Err(e) => CheckResult::unhealthy(format!("Redis cluster connection failed: {}", e))
+                        .with_code("redis.connect")
+                        .with_causes(&e),
+                },
+                Err(e) => CheckResult::unhealthy(format!("Redis cluster client creation failed: {}", e))
+                    .with_code("redis.config")
+                    .with_causes(&e),
+            }
+        })
+    })
+}
+
+/// Create a Redis Sentinel health check
+///
+/// Asks the sentinels for the current master of `master_name`, connects to
+/// it and performs the same round-trip as [`redis_check`].
+pub fn redis_sentinel_check(sentinels: Vec<String>, master_name: String) -> HealthCheck {
+    Box::new(move || {
+        let sentinels = sentinels.clone();
+        let master_name = master_name.clone();
+        Box::pin(async move {
+            let start = clock_now();
+
+            let mut sentinel = match redis::sentinel::Sentinel::build(sentinels) {
+                Ok(sentinel) => sentinel,
+                Err(e) => {
+                    return CheckResult::unhealthy(format!("Redis sentinel configuration invalid: {}", e))
+                        .with_code("redis.config")
+                        .with_causes(&e)
+                }
+            };
+
+            match sentinel.async_master_for(&master_name, None).await {
+                Ok(client) => match client.get_multiplexed_async_connection().await {
+                    Ok(mut con) => redis_round_trip(&mut con, start).await,
+                    Err(e) => CheckResult::unhealthy(format!("Redis connection failed: {}", e))
+                        .with_code("redis.connect")
+                        .with_causes(&e),
+                },
+                Err(e) => CheckResult::unhealthy(format!(
+                    "Redis sentinel master lookup for '{}' failed: {}",
+                    master_name, e
+                ))
+                .with_code("redis.sentinel")
+                .with_causes(&e),
+            }
+        })
+    })
+}
+
+/// Issue the health check command on an established Redis connection
+async fn redis_round_trip(con: &mut impl redis::aio::ConnectionLike, start: Instant) -> CheckResult {
+    // Use GET instead of PING so the command is routed like regular traffic
+    match redis::cmd("GET")
+        .arg("__health_check__")
+        .query_async::<_, Option<String>>(con)
+        .await
+    {
+        Ok(_) => {
+            CheckResult::healthy()
+                .with_elapsed(elapsed_since(start))
+        }
+        Err(e) => CheckResult::unhealthy(format!("Redis check failed: {}", e))
+            .with_code("redis.command")
+            .with_causes(&e),
+    }
+}
+
 /// Create an HTTP endpoint health check
 ///
-/// Makes a GET request to the specified URL
+/// Makes a GET request to the specified URL. The URL is parsed once here and
+/// an invalid one makes every run report `http.config`; use
+/// [`http_check_checked`] to reject it at startup instead. The HTTP client
+/// is created on the first run that succeeds in building one, so a rustls
+/// crypto provider installed after registration still takes effect; until
+/// then runs report `http.config`.
 pub fn http_check(url: String, expected_status: u16) -> HealthCheck {
+    match url::Url::parse(&url) {
+        Ok(url) => http_url_check(url, expected_status, None),
+        Err(e) => invalid_url_check(url, e),
+    }
+}
+
+/// Create an HTTP endpoint health check, validating the URL up front
+///
+/// Like [`http_check`] but fails on an unparseable URL, so configuration
+/// mistakes surface before the service starts serving. The error converts
+/// into [`BuildError`](crate::BuildError) for use alongside
+/// [`try_build`](crate::HealthCheckBuilder::try_build).
+pub fn http_check_checked(url: &str, expected_status: u16) -> Result<HealthCheck, url::ParseError> {
+    Ok(http_url_check(url::Url::parse(url)?, expected_status, None))
+}
+
+/// Create an HTTP endpoint health check that also inspects the body
+///
+/// Healthy only when the status matches and `predicate` accepts the body,
+/// for upstreams that report their real health in the payload, e.g.
+/// `|body| body.contains(r#""status":"ok""#)`. A rejected body is quoted,
+/// truncated, in the failure message.
+pub fn http_check_with_body<P>(url: String, expected_status: u16, predicate: P) -> HealthCheck
+where
+    P: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    match url::Url::parse(&url) {
+        Ok(url) => http_url_check(url, expected_status, Some(Arc::new(predicate))),
+        Err(e) => invalid_url_check(url, e),
+    }
+}
+
+/// Longest body excerpt quoted when a body predicate rejects a response
+const BODY_SNIPPET_CHARS: usize = 200;
+
+type BodyPredicate = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+fn invalid_url_check(url: String, e: url::ParseError) -> HealthCheck {
+    custom_sync_check(move || {
+        CheckResult::unhealthy(format!("Invalid URL '{}': {}", url, e))
+            .with_code("http.config")
+    })
+}
+
+fn http_url_check(
+    url: url::Url,
+    expected_status: u16,
+    predicate: Option<BodyPredicate>,
+) -> HealthCheck {
+    let client = Arc::new(OnceLock::new());
+
     Box::new(move || {
+        let client = client.clone();
         let url = url.clone();
+        let predicate = predicate.clone();
         Box::pin(async move {
-            let start = Instant::now();
+            let start = clock_now();
 
-            match reqwest::get(&url).await {
-                Ok(response) => {
-                    let duration = start.elapsed().as_millis() as u64;
-                    let status = response.status().as_u16();
+            let client = match lazy_http_client(&client, reqwest::Client::builder) {
+                Ok(client) => client,
+                Err(message) => return CheckResult::unhealthy(message).with_code("http.config"),
+            };
 
-                    if status == expected_status {
-                        CheckResult::healthy_with_message(format!("HTTP {} OK", status))
-                            .with_duration(duration)
-                    } else {
-                        CheckResult::unhealthy(format!(
-                            "Expected status {}, got {}",
-                            expected_status, status
-                        ))
+            let response = match client.get(url).send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    return CheckResult::unhealthy(format!("HTTP request failed: {}", e))
+                        .with_code("http.request")
+                        .with_causes(&e)
+                }
+            };
+
+            let status = response.status().as_u16();
+            if status != expected_status {
+                return CheckResult::unhealthy(format!(
+                    "Expected status {}, got {}",
+                    expected_status, status
+                ))
+                .with_code("http.status");
+            }
+
+            if let Some(predicate) = predicate {
+                let body = match response.text().await {
+                    Ok(body) => body,
+                    Err(e) => {
+                        return CheckResult::unhealthy(format!("HTTP body read failed: {}", e))
+                            .with_code("http.request")
+                            .with_causes(&e)
                     }
+                };
+
+                if !predicate(&body) {
+                    let snippet: String = body.chars().take(BODY_SNIPPET_CHARS).collect();
+                    let ellipsis = if snippet.len() < body.len() { "..." } else { "" };
+                    return CheckResult::unhealthy(format!(
+                        "HTTP {} body rejected: {}{}",
+                        status, snippet, ellipsis
+                    ))
+                    .with_code("http.body");
                 }
-                Err(e) => CheckResult::unhealthy(format!("HTTP request failed: {}", e)),
+            }
+
+            CheckResult::healthy_with_message(format!("HTTP {} OK", status))
+                .with_elapsed(elapsed_since(start))
+        })
+    })
+}
+
+/// Build an HTTP client, reporting failure instead of panicking
+///
+/// reqwest panics when no rustls crypto provider is installed, which this
+/// crate leaves to the application, so that case is checked first.
+fn build_http_client(builder: reqwest::ClientBuilder) -> Result<reqwest::Client, String> {
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        return Err("HTTP client creation failed: no TLS crypto provider installed; install \
+                    a rustls provider before creating HTTP checks"
+            .to_string());
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("HTTP client creation failed: {}", e))
+}
+
+/// The client in `cell`, building it with `builder` if none was built yet
+///
+/// A failed build is not cached, so the next call tries again.
+fn lazy_http_client(
+    cell: &OnceLock<reqwest::Client>,
+    builder: impl FnOnce() -> reqwest::ClientBuilder,
+) -> Result<reqwest::Client, String> {
+    if let Some(client) = cell.get() {
+        return Ok(client.clone());
+    }
+
+    let client = build_http_client(builder())?;
+    Ok(cell.get_or_init(|| client).clone())
+}
+
+/// Timeout for [`egress_check`]; a healthy egress path answers well within it
+const EGRESS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Create an egress reachability check
+///
+/// Verifies the service can reach the outside world by requesting
+/// `probe_url`. Any HTTP response counts as reachable, since only the
+/// network path is under test; timeouts and connection errors point at
+/// NAT, firewall or egress policy problems. Results carry a `kind` detail
+/// of `"egress"` and the target `host` to set them apart from dependency
+/// checks.
+///
+/// The HTTP client is created on the first run that succeeds in building
+/// one; until then (e.g. no rustls crypto provider is installed yet) runs
+/// report `egress.config`.
+pub fn egress_check(probe_url: String) -> HealthCheck {
+    let host = reqwest::Url::parse(&probe_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| probe_url.clone());
+    let client = Arc::new(OnceLock::new());
+
+    Box::new(move || {
+        let url = probe_url.clone();
+        let host = host.clone();
+        let client = client.clone();
+        Box::pin(async move {
+            let start = clock_now();
+
+            let builder = || reqwest::Client::builder().timeout(EGRESS_TIMEOUT);
+            let client = match lazy_http_client(&client, builder) {
+                Ok(client) => client,
+                Err(message) => {
+                    return CheckResult::unhealthy(message)
+                        .with_code("egress.config")
+                        .with_detail("kind", "egress")
+                        .with_detail("host", host)
+                }
+            };
+
+            let result = match client.get(&url).send().await {
+                Ok(response) => CheckResult::healthy_with_message(format!(
+                    "Egress to {} reachable (HTTP {})",
+                    host,
+                    response.status().as_u16()
+                ))
+                .with_elapsed(elapsed_since(start)),
+                Err(e) if e.is_timeout() => CheckResult::unhealthy(format!(
+                    "Egress to {} timed out after {}ms",
+                    host,
+                    EGRESS_TIMEOUT.as_millis()
+                ))
+                .with_code("egress.timeout")
+                .with_causes(&e),
+                Err(e) => CheckResult::unhealthy(format!("Egress to {} failed: {}", host, e))
+                    .with_code("egress.unreachable")
+                    .with_causes(&e),
+            };
+
+            result
+                .with_detail("kind", "egress")
+                .with_detail("host", host)
+        })
+    })
+}
+
+/// Create a NATS health check
+///
+/// Flushes the client's pending writes and waits for the server's PONG,
+/// confirming the connection round-trips within `timeout`. Takes the shared
+/// application client rather than opening a new connection.
+#[cfg(feature = "nats")]
+pub fn nats_check(client: async_nats::Client, timeout: Duration) -> HealthCheck {
+    Box::new(move || {
+        let client = client.clone();
+        Box::pin(async move {
+            let start = clock_now();
+
+            match tokio::time::timeout(timeout, client.flush()).await {
+                Ok(Ok(())) => {
+                    CheckResult::healthy()
+                        .with_elapsed(elapsed_since(start))
+                }
+                Ok(Err(e)) => CheckResult::unhealthy(format!("NATS flush failed: {}", e))
+                    .with_code("nats.flush")
+                    .with_causes(&e),
+                Err(_) => CheckResult::unhealthy(format!(
+                    "NATS flush timed out after {}ms",
+                    timeout.as_millis()
+                ))
+                .with_code("nats.timeout"),
+            }
+        })
+    })
+}
+
+/// Create a Kafka consumer group lag check
+///
+/// Compares the group's committed offsets on `topic` with the partition
+/// high-watermarks and sums the difference; partitions without a committed
+/// offset count their whole retained backlog. Reports degraded above 90% of
+/// `max_lag` and unhealthy above it, catching stuck or slow consumers while
+/// the brokers are still reachable. The Kafka client is synchronous, so the
+/// check runs on the blocking thread pool.
+///
+/// The client can't be given a socket timeout, so a hung broker keeps a
+/// lookup running after the probe has timed out. Only one lookup per check
+/// runs at a time; probes arriving meanwhile report `kafka.stalled` instead
+/// of piling up blocking threads.
+#[cfg(feature = "kafka")]
+pub fn kafka_lag_check(
+    brokers: Vec<String>,
+    group: String,
+    topic: String,
+    max_lag: u64,
+) -> HealthCheck {
+    let warning_lag = max_lag - max_lag / 10;
+    let lookup_running = Arc::new(AtomicBool::new(false));
+
+    /// Clears the in-flight flag even if the lookup panics
+    struct LookupGuard<'a>(&'a AtomicBool);
+
+    impl Drop for LookupGuard<'_> {
+        fn drop(&mut self) {
+            self.0.store(false, Ordering::Release);
+        }
+    }
+
+    blocking_check(move || {
+        let start = clock_now();
+
+        if lookup_running.swap(true, Ordering::AcqRel) {
+            return CheckResult::unhealthy("Previous Kafka offset lookup is still running")
+                .with_code("kafka.stalled");
+        }
+        let guard = LookupGuard(&lookup_running);
+        let lookup = kafka_consumer_lag(&brokers, &group, &topic);
+        drop(guard);
+
+        let lag = match lookup {
+            Ok(lag) => lag,
+            Err(e) => {
+                return CheckResult::unhealthy(format!("Kafka offset lookup failed: {}", e))
+                    .with_code("kafka.offsets")
+                    .with_causes(&e)
+            }
+        };
+
+        let result = if lag > max_lag {
+            CheckResult::unhealthy(format!(
+                "Consumer group '{}' lag {} exceeds limit {}",
+                group, lag, max_lag
+            ))
+            .with_code("kafka.lag")
+        } else if lag > warning_lag {
+            CheckResult::degraded(format!(
+                "Consumer group '{}' lag {} is above 90% of limit {}",
+                group, lag, max_lag
+            ))
+            .with_code("kafka.lag")
+        } else {
+            CheckResult::healthy()
+        };
+
+        result
+            .with_elapsed(elapsed_since(start))
+            .with_detail("lag", lag)
+            .with_detail("max_lag", max_lag)
+    })
+}
+
+/// Total lag of `group` on `topic` across partitions
+#[cfg(feature = "kafka")]
+fn kafka_consumer_lag(brokers: &[String], group: &str, topic: &str) -> kafka::Result<u64> {
+    use kafka::client::{FetchOffset, GroupOffsetStorage, KafkaClient, PartitionOffset};
+
+    let mut client = KafkaClient::new(brokers.to_vec());
+    client.set_group_offset_storage(Some(GroupOffsetStorage::Kafka));
+    client.load_metadata(&[topic])?;
+
+    let committed = client.fetch_group_topic_offset(group, topic)?;
+    let earliest = client.fetch_topic_offsets(topic, FetchOffset::Earliest)?;
+    let offset_of = |offsets: &[PartitionOffset], partition: i32| {
+        offsets
+            .iter()
+            .find(|offset| offset.partition == partition)
+            .map_or(-1, |offset| offset.offset)
+    };
+
+    let lag = client
+        .fetch_topic_offsets(topic, FetchOffset::Latest)?
+        .into_iter()
+        .map(|latest| {
+            // Messages removed by retention can't be consumed, and a negative
+            // committed offset (no commit yet) counts only the retained backlog
+            let earliest = offset_of(&earliest, latest.partition).max(0);
+            let consumed = offset_of(&committed, latest.partition).max(earliest);
+            latest.offset.saturating_sub(consumed).max(0) as u64
+        })
+        .sum();
+
+    Ok(lag)
+}
+
+/// Create a process memory health check
+///
+/// Compares the process resident set size (RSS, from `/proc/self/status`)
+/// against `max_bytes`, typically the container memory limit. Reports
+/// degraded above 90% of the limit and unhealthy above the limit, so load can
+/// be shed before the OOM killer steps in. Reports healthy with a "not
+/// supported" message on platforms without procfs, so it never fails the
+/// probe there, and unknown if procfs cannot be read.
+pub fn memory_check(max_bytes: u64) -> HealthCheck {
+    let warning_bytes = max_bytes / 10 * 9;
+
+    Box::new(move || {
+        Box::pin(async move {
+            let rss_bytes = match resident_memory_bytes() {
+                Ok(rss_bytes) => rss_bytes,
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                    return CheckResult::healthy_with_message(
+                        "Memory check not supported on this platform",
+                    )
+                    .with_code("memory.unsupported")
+                }
+                Err(e) => {
+                    return CheckResult::unknown(format!("Memory usage unavailable: {}", e))
+                        .with_code("memory.unavailable")
+                }
+            };
+
+            let result = if rss_bytes > max_bytes {
+                CheckResult::unhealthy(format!(
+                    "Resident memory {} bytes exceeds limit {} bytes",
+                    rss_bytes, max_bytes
+                ))
+                .with_code("memory.limit")
+            } else if rss_bytes > warning_bytes {
+                CheckResult::degraded(format!(
+                    "Resident memory {} bytes is above 90% of limit {} bytes",
+                    rss_bytes, max_bytes
+                ))
+                .with_code("memory.pressure")
+            } else {
+                CheckResult::healthy()
+            };
+
+            result
+                .with_detail("rss_bytes", rss_bytes)
+                .with_detail("limit_bytes", max_bytes)
+        })
+    })
+}
+
+/// Read the resident set size of the current process
+#[cfg(target_os = "linux")]
+fn resident_memory_bytes() -> std::io::Result<u64> {
+    let status = std::fs::read_to_string("/proc/self/status")?;
+
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kib| kib * 1024)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "VmRSS not found"))
+}
+
+/// Read the resident set size of the current process
+#[cfg(not(target_os = "linux"))]
+fn resident_memory_bytes() -> std::io::Result<u64> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "procfs is not available on this platform",
+    ))
+}
+
+/// Liveness signal ticked periodically by the application's main loop
+///
+/// Cheap to clone and to tick: a tick is a single atomic store, so hot loops
+/// can call [`Heartbeat::tick`] freely. Creation counts as the first tick.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    origin: Instant,
+    last_tick_ms: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    /// Create a heartbeat that has just ticked
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            last_tick_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Record that the application is making progress
+    pub fn tick(&self) {
+        let elapsed = self.origin.elapsed().as_millis() as u64;
+        self.last_tick_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    /// Time since the most recent tick
+    pub fn since_last_tick(&self) -> Duration {
+        let now = self.origin.elapsed().as_millis() as u64;
+        let last_tick = self.last_tick_ms.load(Ordering::Relaxed);
+        Duration::from_millis(now.saturating_sub(last_tick))
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create a health check that fails when `heartbeat` goes stale
+///
+/// Reports unhealthy if no tick has occurred within `max_staleness`.
+/// Register it as a liveness check so a process whose tasks are wedged, but
+/// which still serves HTTP, gets restarted.
+pub fn heartbeat_check(heartbeat: Heartbeat, max_staleness: Duration) -> HealthCheck {
+    Box::new(move || {
+        let since_last_tick = heartbeat.since_last_tick();
+        Box::pin(async move {
+            let result = if since_last_tick > max_staleness {
+                CheckResult::unhealthy(format!(
+                    "No heartbeat for {}ms (limit {}ms)",
+                    since_last_tick.as_millis(),
+                    max_staleness.as_millis()
+                ))
+                .with_code("heartbeat.stale")
+            } else {
+                CheckResult::healthy()
+            };
+
+            result.with_detail("since_last_tick_ms", since_last_tick.as_millis() as u64)
+        })
+    })
+}
+
+/// Create a minimum-version compatibility check
+///
+/// Runs `fetch` to obtain the version of a connected dependency and reports
+/// unhealthy when it is older than `min`, catching incompatible backends
+/// before they fail requests. Both versions are reported in details.
+pub fn version_check<F, Fut>(fetch: F, min: semver::Version) -> HealthCheck
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = semver::Version> + Send + 'static,
+{
+    let min = Arc::new(min);
+
+    Box::new(move || {
+        let fetch = fetch();
+        let min = min.clone();
+        Box::pin(async move {
+            let start = clock_now();
+            let version = fetch.await;
+
+            let result = if version >= *min {
+                CheckResult::healthy().with_elapsed(elapsed_since(start))
+            } else {
+                CheckResult::unhealthy(format!(
+                    "Version {} is older than required {}",
+                    version, min
+                ))
+                .with_code("version.incompatible")
+            };
+
+            result
+                .with_detail("version", version.to_string())
+                .with_detail("min_version", min.to_string())
+        })
+    })
+}
+
+/// Create a config check that verifies a sentinel value
+///
+/// Runs `fetch` against a config or feature-flag service and compares the
+/// returned value with `expected`, catching a service that is up but serving
+/// missing or wrong configuration. A mismatch reports unhealthy with both
+/// values in details.
+pub fn config_check<F, Fut, E>(fetch: F, expected: String) -> HealthCheck
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String, E>> + Send + 'static,
+    E: fmt::Display,
+{
+    let expected = Arc::new(expected);
+
+    Box::new(move || {
+        let fetch = fetch();
+        let expected = expected.clone();
+        Box::pin(async move {
+            let start = clock_now();
+
+            match fetch.await {
+                Ok(actual) if actual == *expected => {
+                    CheckResult::healthy().with_elapsed(elapsed_since(start))
+                }
+                Ok(actual) => CheckResult::unhealthy("Config value does not match expected value")
+                    .with_code("config.mismatch")
+                    .with_detail("expected", expected.as_str())
+                    .with_detail("actual", actual),
+                Err(e) => CheckResult::unhealthy(format!("Config fetch failed: {}", e))
+                    .with_code("config.fetch"),
+            }
+        })
+    })
+}
+
+/// How long the self-test waits for the runtime to schedule its task
+const SELFTEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Minimal process self-test for the liveness probe
+///
+/// Spawns a task and checks the runtime runs it promptly, so a process that
+/// still accepts connections but can no longer schedule work fails
+/// liveness.
+pub(crate) fn selftest_check() -> HealthCheck {
+    Box::new(|| {
+        Box::pin(async {
+            let start = clock_now();
+            let task = tokio::spawn(async {});
+
+            match tokio::time::timeout(SELFTEST_TIMEOUT, task).await {
+                Ok(Ok(())) => CheckResult::healthy().with_elapsed(elapsed_since(start)),
+                Ok(Err(e)) => CheckResult::unhealthy(format!("Self-test task failed: {}", e))
+                    .with_code("selftest.task"),
+                Err(_) => CheckResult::unhealthy(format!(
+                    "Runtime did not schedule self-test within {}ms",
+                    SELFTEST_TIMEOUT.as_millis()
+                ))
+                .with_code("selftest.schedule"),
             }
         })
     })
 }
 
 /// Create a custom health check from an async function
+///
+/// Panics raised by `f` or its future are reported as unhealthy results
+/// rather than propagating into the probe handler.
 pub fn custom_check<F, Fut>(f: F) -> HealthCheck
 where
     F: Fn() -> Fut + Send + Sync + 'static,
@@ -103,3 +1185,540 @@ where
 {
     Box::new(move || Box::pin(f()))
 }
+
+/// Create a custom health check over application state provided later
+///
+/// Reports unknown until `state` has been set, then runs `f` with a clone of
+/// it on each evaluation. See the [module docs](self#application-state).
+pub fn state_check<T, F, Fut>(state: Arc<OnceLock<T>>, f: F) -> HealthCheck
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = CheckResult> + Send + 'static,
+{
+    Box::new(move || match state.get() {
+        Some(state) => Box::pin(f(state.clone())),
+        None => Box::pin(async {
+            CheckResult::unknown("application state not yet available")
+                .with_code("check.state_unset")
+        }),
+    })
+}
+
+/// Create a custom health check from a synchronous function
+///
+/// `f` runs inline on the probe task, so it must be cheap and non-blocking,
+/// e.g. inspecting an in-memory flag or parsed config. Work that blocks on
+/// I/O or locks for any noticeable time stalls the async worker running the
+/// probe; use [`blocking_check`] for it instead.
+pub fn custom_sync_check<F>(f: F) -> HealthCheck
+where
+    F: Fn() -> CheckResult + Send + Sync + 'static,
+{
+    Box::new(move || {
+        let result = f();
+        Box::pin(async move { result })
+    })
+}
+
+/// Degrade a healthy check that exceeds a latency budget
+///
+/// Uses the duration reported by the check, falling back to the measured
+/// wall-clock time when it reports none. Unhealthy and unknown results are
+/// passed through unchanged.
+pub fn with_latency_budget(check: HealthCheck, budget: Duration) -> HealthCheck {
+    let check = Arc::new(check);
+
+    Box::new(move || {
+        let check = check.clone();
+        Box::pin(async move {
+            let start = clock_now();
+            let result = run_check(&check).await;
+            let elapsed = result.duration().unwrap_or_else(|| elapsed_since(start));
+
+            if result.status != CheckStatus::Healthy || elapsed <= budget {
+                return result;
+            }
+
+            CheckResult {
+                status: CheckStatus::Degraded,
+                message: Some(format!(
+                    "Took {}ms, exceeding latency budget of {}ms",
+                    elapsed.as_millis(),
+                    budget.as_millis()
+                )),
+                code: Some("check.latency_budget".to_string()),
+                ..result
+            }
+            .with_elapsed(elapsed)
+        })
+    })
+}
+
+tokio::task_local! {
+    /// Deadline of the innermost enclosing [`with_timeout`]
+    static DEADLINE: Instant;
+}
+
+/// Fail a check that does not complete within `timeout`
+///
+/// The check future is dropped on expiry and an unhealthy result is
+/// reported in its place.
+pub fn with_timeout(check: HealthCheck, timeout: Duration) -> HealthCheck {
+    let check = Arc::new(check);
+
+    Box::new(move || {
+        let check = check.clone();
+        Box::pin(async move {
+            let deadline = Instant::now() + timeout;
+            let deadline = DEADLINE.try_with(|outer| deadline.min(*outer)).unwrap_or(deadline);
+            let run = DEADLINE.scope(deadline, run_check(&check));
+
+            match tokio::time::timeout(timeout, run).await {
+                Ok(result) => result,
+                Err(_) => CheckResult::unhealthy(format!(
+                    "Timed out after {}ms",
+                    timeout.as_millis()
+                ))
+                .with_code("check.timeout")
+                .with_elapsed(timeout),
+            }
+        })
+    })
+}
+
+/// Retry an unhealthy check with exponential backoff
+///
+/// Runs the check up to `attempts` times, waiting `base_delay`, then twice
+/// that, and so on, capped at `max_delay`. A retry that is not expected to
+/// finish before an enclosing [`with_timeout`] (or per-check timeout)
+/// expires, judging by how long the previous attempt took, is skipped, so
+/// the last real result is reported instead of a timeout. The result
+/// carries an `attempts` detail.
+pub fn with_retry_backoff(
+    check: HealthCheck,
+    attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+) -> HealthCheck {
+    retry_backoff(check, attempts, base_delay, max_delay, false)
+}
+
+/// Retry an unhealthy check with exponential backoff and full jitter
+///
+/// Like [`with_retry_backoff`], but each wait is drawn uniformly from zero
+/// up to the backoff delay, so replicas probing the same flickering
+/// dependency don't retry in lockstep.
+pub fn with_retry_backoff_jitter(
+    check: HealthCheck,
+    attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+) -> HealthCheck {
+    retry_backoff(check, attempts, base_delay, max_delay, true)
+}
+
+fn retry_backoff(
+    check: HealthCheck,
+    attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+) -> HealthCheck {
+    let check = Arc::new(check);
+    let attempts = attempts.max(1);
+
+    Box::new(move || {
+        let check = check.clone();
+        Box::pin(async move {
+            let start = clock_now();
+            let deadline = DEADLINE.try_with(|deadline| *deadline).ok();
+            let mut delay = base_delay.min(max_delay);
+            let mut attempt = 1;
+
+            loop {
+                let attempt_start = Instant::now();
+                let result = run_check(&check).await;
+                let last_attempt = attempt_start.elapsed();
+
+                if result.status != CheckStatus::Unhealthy || attempt >= attempts {
+                    let elapsed = elapsed_since(start);
+                    return result.with_detail("attempts", attempt).with_elapsed(elapsed);
+                }
+
+                let wait = if jitter { random_fraction(delay) } else { delay };
+                if deadline
+                    .is_some_and(|deadline| Instant::now() + wait + last_attempt >= deadline)
+                {
+                    let elapsed = elapsed_since(start);
+                    return result.with_detail("attempts", attempt).with_elapsed(elapsed);
+                }
+
+                tokio::time::sleep(wait).await;
+                delay = delay.saturating_mul(2).min(max_delay);
+                attempt += 1;
+            }
+        })
+    })
+}
+
+/// A uniformly random duration between zero and `max`
+fn random_fraction(max: Duration) -> Duration {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let random = RandomState::new().build_hasher().finish();
+    max.mul_f64(random as f64 / u64::MAX as f64)
+}
+
+/// Cache a check's result for `ttl`, refreshing in the background once stale
+///
+/// The first run executes inline. After that, a fresh result is served from
+/// cache; a stale one is still served immediately while a single refresh
+/// runs in the background (stale-while-revalidate), so expensive checks
+/// never slow down the probe. Served results carry an `age_ms` detail.
+///
+/// A background refresh taking longer than `ttl` is abandoned and cached as
+/// unhealthy (`check.timeout`), so a hung dependency can't leave the last
+/// good result in place indefinitely.
+pub fn cache_for(check: HealthCheck, ttl: Duration) -> HealthCheck {
+    let check = Arc::new(check);
+    let cached: Arc<Mutex<Option<(CheckResult, Instant)>>> = Arc::new(Mutex::new(None));
+    let refreshing = Arc::new(AtomicBool::new(false));
+
+    Box::new(move || {
+        let check = check.clone();
+        let cached = cached.clone();
+        let refreshing = refreshing.clone();
+        Box::pin(async move {
+            let entry = cached.lock().unwrap_or_else(PoisonError::into_inner).clone();
+
+            let Some((result, at)) = entry else {
+                let result = run_check(&check).await;
+                *cached.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some((result.clone(), Instant::now()));
+                return result.with_detail("age_ms", 0);
+            };
+
+            let age = at.elapsed();
+            if age >= ttl && !refreshing.swap(true, Ordering::AcqRel) {
+                tokio::spawn(async move {
+                    // Detached from the probe's timeout, so bound the refresh here
+                    let result = match tokio::time::timeout(ttl, run_check(&check)).await {
+                        Ok(result) => result,
+                        Err(_) => CheckResult::unhealthy(format!(
+                            "Refresh timed out after {}ms",
+                            ttl.as_millis()
+                        ))
+                        .with_code("check.timeout")
+                        .with_elapsed(ttl),
+                    };
+                    *cached.lock().unwrap_or_else(PoisonError::into_inner) =
+                        Some((result, Instant::now()));
+                    refreshing.store(false, Ordering::Release);
+                });
+            }
+
+            result.with_detail("age_ms", age.as_millis() as u64)
+        })
+    })
+}
+
+/// Create a health check from a blocking function
+///
+/// `f` runs on Tokio's blocking thread pool via `spawn_blocking`, so calls
+/// into synchronous libraries don't starve the async executor. A panic in
+/// `f` is reported as unhealthy.
+pub fn blocking_check<F>(f: F) -> HealthCheck
+where
+    F: Fn() -> CheckResult + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+
+    Box::new(move || {
+        let f = f.clone();
+        Box::pin(async move {
+            match tokio::task::spawn_blocking(move || f()).await {
+                Ok(result) => result,
+                Err(e) if e.is_panic() => panicked(e.into_panic()),
+                Err(e) => CheckResult::unknown(format!("Blocking check did not complete: {}", e)),
+            }
+        })
+    })
+}
+
+/// Combine checks into one that is healthy if any member is healthy
+///
+/// Members run concurrently. Useful for redundant upstreams. If no member is
+/// healthy the result is degraded when some member is degraded, otherwise
+/// unhealthy. The message lists which members (by index) passed.
+pub fn any_of(checks: Vec<HealthCheck>) -> HealthCheck {
+    combine(checks, |results| {
+        if results.iter().any(|result| result.status == CheckStatus::Healthy) {
+            CheckStatus::Healthy
+        } else if results.iter().any(|result| result.status == CheckStatus::Degraded) {
+            CheckStatus::Degraded
+        } else {
+            CheckStatus::Unhealthy
+        }
+    })
+}
+
+/// Combine checks into one that is healthy only if every member is healthy
+///
+/// Members run concurrently. If not all members are healthy the result is
+/// unhealthy when some member is unhealthy or unknown, otherwise degraded.
+/// The message lists which members (by index) passed.
+pub fn all_of(checks: Vec<HealthCheck>) -> HealthCheck {
+    combine(checks, |results| {
+        if results.iter().all(|result| result.status == CheckStatus::Healthy) {
+            CheckStatus::Healthy
+        } else if results
+            .iter()
+            .all(|result| matches!(result.status, CheckStatus::Healthy | CheckStatus::Degraded))
+        {
+            CheckStatus::Degraded
+        } else {
+            CheckStatus::Unhealthy
+        }
+    })
+}
+
+/// Invert a check: healthy becomes unhealthy and unhealthy becomes healthy
+///
+/// For conditions that are healthy when something is absent, e.g. a
+/// maintenance flag that should not be set. Degraded and unknown results
+/// are passed through. The message notes the inversion and the original
+/// message; an inverted failure carries code `check.inverted`, and an
+/// inverted success keeps the original details under `inverted_from`.
+pub fn not(check: HealthCheck) -> HealthCheck {
+    let check = Arc::new(check);
+
+    Box::new(move || {
+        let check = check.clone();
+        Box::pin(async move {
+            let mut result = run_check(&check).await;
+            let original = result.message.as_deref().unwrap_or(result.status.as_str());
+            let message = format!("Inverted: {}", original);
+
+            match result.status {
+                CheckStatus::Healthy => CheckResult {
+                    status: CheckStatus::Unhealthy,
+                    message: Some(message),
+                    code: Some("check.inverted".to_string()),
+                    ..result
+                },
+                CheckStatus::Unhealthy => {
+                    // Failure details such as `causes` would be misleading on a pass
+                    let details = std::mem::take(&mut result.details);
+                    let inverted = CheckResult {
+                        status: CheckStatus::Healthy,
+                        message: Some(message),
+                        code: None,
+                        ..result
+                    };
+
+                    if details.is_empty() {
+                        inverted
+                    } else {
+                        let details: serde_json::Map<_, _> = details.into_iter().collect();
+                        inverted.with_detail("inverted_from", details)
+                    }
+                }
+                CheckStatus::Degraded | CheckStatus::Unknown => result,
+            }
+        })
+    })
+}
+
+/// Run member checks concurrently and fold their results with `aggregate`
+fn combine<F>(checks: Vec<HealthCheck>, aggregate: F) -> HealthCheck
+where
+    F: Fn(&[CheckResult]) -> CheckStatus + Send + Sync + 'static,
+{
+    let checks = Arc::new(checks);
+    let aggregate = Arc::new(aggregate);
+
+    Box::new(move || {
+        let checks = checks.clone();
+        let aggregate = aggregate.clone();
+        Box::pin(async move {
+            let start = clock_now();
+            let results = futures::future::join_all(checks.iter().map(run_check)).await;
+
+            let passed = results
+                .iter()
+                .enumerate()
+                .filter(|(_, result)| result.status == CheckStatus::Healthy)
+                .map(|(index, _)| index.to_string())
+                .collect::<Vec<_>>();
+            let message = if passed.is_empty() {
+                format!("0 of {} members passed", results.len())
+            } else {
+                format!(
+                    "{} of {} members passed ({})",
+                    passed.len(),
+                    results.len(),
+                    passed.join(", ")
+                )
+            };
+
+            let members = results
+                .iter()
+                .map(|result| serde_json::to_value(result).unwrap_or_default())
+                .collect::<Vec<_>>();
+
+            CheckResult {
+                status: aggregate(&results),
+                message: Some(message),
+                ..CheckResult::healthy()
+            }
+            .with_elapsed(elapsed_since(start))
+            .with_detail("members", members)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_check;
+
+    #[tokio::test]
+    async fn cache_for_bounds_a_hung_refresh() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = calls.clone();
+        let check: HealthCheck = Box::new(move || {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if call > 0 {
+                    std::future::pending::<()>().await;
+                }
+                CheckResult::healthy()
+            })
+        });
+        let check = cache_for(check, Duration::from_millis(50));
+
+        assert_eq!(check().await.status, CheckStatus::Healthy);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+
+        // Stale: served as-is while the refresh hangs, then times out
+        assert_eq!(check().await.status, CheckStatus::Healthy);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+
+        let result = check().await;
+        assert_eq!(result.status, CheckStatus::Unhealthy);
+        assert_eq!(result.code.as_deref(), Some("check.timeout"));
+    }
+
+    #[tokio::test]
+    async fn http_check_reports_a_missing_tls_provider() {
+        let result = http_check("https://example.com".to_string(), 200)().await;
+
+        assert_eq!(result.status, CheckStatus::Unhealthy);
+        assert_eq!(result.code.as_deref(), Some("http.config"));
+    }
+
+    #[tokio::test]
+    async fn egress_check_reports_a_missing_tls_provider() {
+        let result = egress_check("https://example.com".to_string())().await;
+
+        assert_eq!(result.status, CheckStatus::Unhealthy);
+        assert_eq!(result.code.as_deref(), Some("egress.config"));
+    }
+
+    #[test]
+    fn redis_config_debug_redacts_url_credentials() {
+        let config = RedisConfig::new("redis://:secret@cache:6379/0");
+
+        let debug = format!("{:?}", config);
+
+        assert!(!debug.contains("secret"), "{}", debug);
+        assert!(debug.contains("redis://:redacted@cache:6379/0"), "{}", debug);
+    }
+
+    #[tokio::test]
+    async fn not_moves_failure_details_under_inverted_from() {
+        let check = not(mock_check(
+            CheckResult::unhealthy("flag set").with_detail("causes", vec!["set by operator"]),
+        ));
+
+        let result = run_check(&check).await;
+
+        assert_eq!(result.status, CheckStatus::Healthy);
+        assert!(!result.details.contains_key("causes"));
+        assert_eq!(
+            result.details["inverted_from"],
+            serde_json::json!({ "causes": ["set by operator"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_backoff_skips_a_retry_that_would_time_out() {
+        let check = with_timeout(
+            with_retry_backoff(
+                Box::new(|| {
+                    Box::pin(async {
+                        tokio::time::sleep(Duration::from_millis(60)).await;
+                        CheckResult::unhealthy("down")
+                    })
+                }),
+                5,
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+            ),
+            Duration::from_millis(100),
+        );
+
+        let result = run_check(&check).await;
+
+        assert_eq!(result.message.as_deref(), Some("down"));
+        assert_eq!(result.details["attempts"], 1);
+    }
+
+    #[tokio::test]
+    async fn combinators_map_member_statuses() {
+        let status = |check: HealthCheck| async move { run_check(&check).await.status };
+        let members = |results: Vec<CheckResult>| -> Vec<HealthCheck> {
+            results.into_iter().map(mock_check).collect()
+        };
+
+        let mixed = || members(vec![CheckResult::healthy(), CheckResult::unhealthy("down")]);
+        let impaired = || members(vec![CheckResult::degraded("slow"), CheckResult::unknown("?")]);
+
+        assert_eq!(status(any_of(mixed())).await, CheckStatus::Healthy);
+        assert_eq!(status(any_of(impaired())).await, CheckStatus::Degraded);
+        assert_eq!(
+            status(any_of(members(vec![CheckResult::unhealthy("down")]))).await,
+            CheckStatus::Unhealthy
+        );
+
+        assert_eq!(
+            status(all_of(members(vec![CheckResult::healthy(), CheckResult::healthy()]))).await,
+            CheckStatus::Healthy
+        );
+        assert_eq!(
+            status(all_of(members(vec![CheckResult::healthy(), CheckResult::degraded("slow")])))
+                .await,
+            CheckStatus::Degraded
+        );
+        assert_eq!(status(all_of(mixed())).await, CheckStatus::Unhealthy);
+        assert_eq!(status(all_of(impaired())).await, CheckStatus::Unhealthy);
+
+        let inverted = run_check(&not(mock_check(CheckResult::healthy()))).await;
+        assert_eq!(inverted.status, CheckStatus::Unhealthy);
+        assert_eq!(inverted.code.as_deref(), Some("check.inverted"));
+        assert_eq!(
+            status(not(mock_check(CheckResult::unhealthy("down")))).await,
+            CheckStatus::Healthy
+        );
+        assert_eq!(
+            status(not(mock_check(CheckResult::degraded("slow")))).await,
+            CheckStatus::Degraded
+        );
+        assert_eq!(
+            status(not(mock_check(CheckResult::unknown("?")))).await,
+            CheckStatus::Unknown
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Time source abstraction for deterministic timestamps and durations
+
+use chrono::{DateTime, Utc};
+use std::time::Instant;
+
+/// Source of wall-clock and monotonic time
+///
+/// The default [`SystemClock`] reads the real clocks. Swap in a controllable
+/// implementation (such as `testing::MockClock`) via
+/// [`HealthCheckBuilder::with_clock`](crate::HealthCheckBuilder::with_clock)
+/// to make response timestamps and evaluation timings deterministic.
+pub trait Clock: Send + Sync {
+    /// Current wall-clock time, used for response timestamps
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current monotonic time, used for measuring durations
+    fn instant(&self) -> Instant;
+}
+
+/// Clock backed by the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
@@ -0,0 +1,75 @@
+//! Declarative health check configuration
+//!
+//! A [`HealthConfig`] selects and tunes registered checks by name, so which
+//! checks run per environment can come from the application's config files
+//! rather than code. Apply it with
+//! [`HealthCheckBuilder::apply_config`](crate::HealthCheckBuilder::apply_config)
+//! after the checks are registered:
+//!
+//! ```toml
+//! default_timeout_ms = 2000
+//!
+//! [checks.database]
+//! timeout_ms = 500
+//!
+//! [checks.search]
+//! critical = false
+//!
+//! [checks.billing]
+//! enabled = false
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Health check settings keyed by check name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Timeout for checks without one of their own, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_timeout_ms: Option<u64>,
+    /// Per-check settings; checks not listed keep their registration
+    #[serde(default)]
+    pub checks: HashMap<String, CheckConfig>,
+}
+
+/// Settings for one registered check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckConfig {
+    /// Whether the check is registered at all
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Timeout in milliseconds, overriding the registered one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Whether the check can fail readiness, overriding the registration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<bool>,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_ms: None,
+            critical: None,
+        }
+    }
+}
+
+impl HealthConfig {
+    pub(crate) fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout_ms.map(Duration::from_millis)
+    }
+}
+
+impl CheckConfig {
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
@@ -0,0 +1,84 @@
+//! Middleware exposing the cached health status on application responses
+
+use crate::response::{CheckStatus, HealthResponse};
+use axum::http::{HeaderName, HeaderValue, Request, Response};
+use futures::future::BoxFuture;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Header carrying the last known aggregate status
+pub const HEALTH_STATUS_HEADER: HeaderName = HeaderName::from_static("x-health-status");
+
+/// Layer that stamps `X-Health-Status` onto every response
+///
+/// The value is the aggregate status of the most recent readiness
+/// evaluation, or `unknown` before the first one. Checks are never run on
+/// the request path; pair with
+/// [`spawn_background_checks`](crate::routes::HealthRoutes::spawn_background_checks)
+/// to keep the value fresh. Created by
+/// [`HealthRoutes::status_layer`](crate::routes::HealthRoutes::status_layer).
+#[derive(Clone)]
+pub struct HealthStatusLayer {
+    latest: Arc<Mutex<Option<HealthResponse>>>,
+}
+
+impl HealthStatusLayer {
+    pub(crate) fn new(latest: Arc<Mutex<Option<HealthResponse>>>) -> Self {
+        Self { latest }
+    }
+}
+
+impl<S> Layer<S> for HealthStatusLayer {
+    type Service = HealthStatusService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HealthStatusService {
+            inner,
+            latest: self.latest.clone(),
+        }
+    }
+}
+
+/// Service produced by [`HealthStatusLayer`]
+#[derive(Clone)]
+pub struct HealthStatusService<S> {
+    inner: S,
+    latest: Arc<Mutex<Option<HealthResponse>>>,
+}
+
+impl<S> HealthStatusService<S> {
+    /// Status of the most recent evaluation
+    fn status(&self) -> CheckStatus {
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map_or(CheckStatus::Unknown, |response| response.status)
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for HealthStatusService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let value = HeaderValue::from_static(self.status().as_str());
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let mut response = future.await?;
+            response.headers_mut().insert(HEALTH_STATUS_HEADER, value);
+            Ok(response)
+        })
+    }
+}
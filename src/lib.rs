@@ -2,14 +2,19 @@
 //!
 //! This library provides standardized health check patterns for:
 //! - Kubernetes liveness and readiness probes
-//! - Composable health check builders
-//! - Built-in checks for PostgreSQL, Redis, HTTP endpoints
-//! - Axum integration helpers
+//! - Composable health check builders, configurable per environment via [`HealthConfig`]
+//! - Built-in checks for PostgreSQL, Redis (TLS with the `redis-tls` feature), HTTP endpoints,
+//!   egress reachability, NATS (`nats` feature) and Kafka consumer lag (`kafka` feature)
+//! - Axum integration helpers (`axum` feature, enabled by default)
+//! - Mock checks for tests (`testing` feature)
+//! - OpenAPI schemas for the endpoints (`openapi` feature)
+//! - Check metrics in a Prometheus registry (`prometheus` feature)
 //!
 //! # Example
 //!
 //! ```rust,no_run
 //! use pleme_health::{HealthCheckBuilder, postgres_check, redis_check};
+//! # #[cfg(feature = "axum")]
 //! use axum::Router;
 //! use sqlx::PgPool;
 //!
@@ -21,6 +26,7 @@
 //!     .build();
 //!
 //! // Add to Axum router
+//! # #[cfg(feature = "axum")]
 //! let app = Router::new()
 //!     .merge(health.routes());
 //! # }
@@ -28,11 +34,41 @@
 
 pub mod checks;
 pub mod builder;
+pub mod clock;
+pub mod config;
+#[cfg(feature = "axum")]
+pub mod layer;
+#[cfg(feature = "axum")]
+pub mod logging;
+#[cfg(feature = "prometheus")]
+mod metrics;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod response;
 pub mod routes;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 // Re-export commonly used types
-pub use builder::HealthCheckBuilder;
-pub use response::{HealthResponse, CheckStatus};
-pub use checks::{postgres_check, redis_check, http_check};
+pub use builder::{BuildError, HealthCheckBuilder};
+pub use clock::{Clock, SystemClock};
+pub use config::{CheckConfig, HealthConfig};
+#[cfg(feature = "axum")]
+pub use layer::HealthStatusLayer;
+pub use response::{
+    BuildInfo, CheckResult, CheckStatus, DurationUnit, HealthResponse, HealthSummary, Timestamp,
+    TimestampFormat,
+};
+pub use checks::{
+    all_of, any_of, config_check, egress_check, heartbeat_check, http_check, http_check_checked,
+    http_check_with_body, memory_check, not, postgres_check, redis_check, redis_check_with_config,
+    redis_cluster_check, redis_sentinel_check, version_check, FailurePolicy, Heartbeat, Probe,
+    RedisConfig,
+};
+#[cfg(feature = "axum")]
 pub use routes::health_routes;
+
+#[cfg(feature = "kafka")]
+pub use checks::kafka_lag_check;
+#[cfg(feature = "nats")]
+pub use checks::nats_check;
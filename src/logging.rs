@@ -0,0 +1,92 @@
+//! Structured logging of readiness probes
+
+use crate::response::{CheckStatus, HealthResponse};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// When to log a readiness probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequestLogging {
+    /// Log only probes whose overall status differs from the last logged one
+    #[default]
+    OnChange,
+    /// Log status changes plus every `n`th probe
+    Sampled(u64),
+    /// Log every probe
+    All,
+}
+
+/// Emits one `tracing` event per logged readiness probe
+pub(crate) struct ProbeLogger {
+    policy: RequestLogging,
+    last_status: Mutex<Option<CheckStatus>>,
+    probes: AtomicU64,
+}
+
+impl ProbeLogger {
+    pub(crate) fn new(policy: RequestLogging) -> Self {
+        Self {
+            policy,
+            last_status: Mutex::new(None),
+            probes: AtomicU64::new(0),
+        }
+    }
+
+    /// Log the outcome of a probe if the policy selects it
+    pub(crate) fn log(&self, response: &HealthResponse, remote_addr: Option<SocketAddr>) {
+        let probe = self.probes.fetch_add(1, Ordering::Relaxed);
+        let previous = self
+            .last_status
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(response.status);
+        let changed = previous != Some(response.status);
+
+        let selected = match self.policy {
+            RequestLogging::OnChange => changed,
+            RequestLogging::Sampled(n) => changed || probe.is_multiple_of(n.max(1)),
+            RequestLogging::All => true,
+        };
+
+        if !selected {
+            return;
+        }
+
+        let checks = response
+            .checks
+            .iter()
+            .map(|(name, result)| {
+                let entry = serde_json::json!({
+                    "status": result.status,
+                    "duration_ms": result.duration().map(|duration| duration.as_millis() as u64),
+                });
+                (name.clone(), entry)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let checks = serde_json::Value::Object(checks).to_string();
+        let remote_addr = remote_addr.map(|addr| addr.to_string());
+
+        if response.status == CheckStatus::Healthy {
+            tracing::info!(
+                service = %response.service,
+                status = ?response.status,
+                changed,
+                remote_addr,
+                total_duration_ms = response.total_duration_ms,
+                checks = %checks,
+                "readiness probe evaluated"
+            );
+        } else {
+            tracing::warn!(
+                service = %response.service,
+                status = ?response.status,
+                changed,
+                remote_addr,
+                total_duration_ms = response.total_duration_ms,
+                checks = %checks,
+                "readiness probe evaluated"
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Prometheus metrics for check results (`prometheus` feature)
+
+use crate::response::{CheckResult, CheckStatus};
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+
+const STATUSES: [CheckStatus; 4] = [
+    CheckStatus::Healthy,
+    CheckStatus::Degraded,
+    CheckStatus::Unhealthy,
+    CheckStatus::Unknown,
+];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct CheckLabels {
+    check: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct StatusLabels {
+    check: String,
+    status: String,
+}
+
+/// Per-check metrics, updated every time a check runs
+#[derive(Clone)]
+pub(crate) struct CheckMetrics {
+    status: Family<StatusLabels, Gauge>,
+    duration: Family<CheckLabels, Histogram, fn() -> Histogram>,
+}
+
+impl CheckMetrics {
+    pub(crate) fn new() -> Self {
+        Self {
+            status: Family::default(),
+            duration: Family::new_with_constructor(duration_histogram),
+        }
+    }
+
+    /// Register clones of the metrics; they share state with the originals
+    pub(crate) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "health_check_status",
+            "Current status of each health check (1 for the active status)",
+            self.status.clone(),
+        );
+        registry.register(
+            "health_check_duration_seconds",
+            "Duration of health check executions",
+            self.duration.clone(),
+        );
+    }
+
+    /// Record the outcome of one check execution
+    pub(crate) fn observe(&self, name: &str, result: &CheckResult) {
+        for status in STATUSES {
+            let labels = StatusLabels {
+                check: name.to_string(),
+                status: status.as_str().to_string(),
+            };
+            self.status
+                .get_or_create(&labels)
+                .set(i64::from(status == result.status));
+        }
+
+        if let Some(duration) = result.duration() {
+            self.duration
+                .get_or_create(&CheckLabels {
+                    check: name.to_string(),
+                })
+                .observe(duration.as_secs_f64());
+        }
+    }
+}
+
+/// Buckets from 1ms to roughly 16s
+fn duration_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.001, 2.0, 15))
+}
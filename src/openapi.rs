@@ -0,0 +1,34 @@
+//! OpenAPI documentation for the health endpoints
+//!
+//! Enabled with the `openapi` feature.
+
+use crate::response::{BuildInfo, CheckResult, CheckStatus, HealthResponse, HealthSummary};
+use utoipa::OpenApi;
+
+/// OpenAPI description of `/health` and `/ready` and their response types
+///
+/// Merge it into a service's own document:
+///
+/// ```rust
+/// use pleme_health::openapi::HealthApi;
+/// use utoipa::OpenApi;
+///
+/// #[derive(OpenApi)]
+/// #[openapi(info(title = "my-service"))]
+/// struct ApiDoc;
+///
+/// let mut doc = ApiDoc::openapi();
+/// pleme_health::openapi::register(&mut doc);
+/// ```
+#[derive(OpenApi)]
+#[openapi(
+    paths(crate::routes::http::health_endpoint, crate::routes::http::readiness_endpoint),
+    components(schemas(HealthResponse, CheckResult, CheckStatus, HealthSummary, BuildInfo)),
+    tags((name = "health", description = "Liveness and readiness probes"))
+)]
+pub struct HealthApi;
+
+/// Add the health endpoints and schemas to an existing OpenAPI document
+pub fn register(openapi: &mut utoipa::openapi::OpenApi) {
+    openapi.merge(HealthApi::openapi());
+}
@@ -1,23 +1,62 @@
 //! Health check response types
 
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use crate::clock::{Clock, SystemClock};
+#[cfg(feature = "axum")]
+use axum::http::StatusCode;
+use chrono::{DateTime, TimeZone, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::ops::Deref;
+use std::time::Duration;
 
 /// Health check status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Ordered by severity, `Healthy < Degraded < Unknown < Unhealthy`, so the
+/// aggregate status of several checks is the `max()` of theirs. An unknown
+/// critical check therefore makes the service unready (503), where it used
+/// to be ignored by the aggregate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum CheckStatus {
     /// Check passed
     Healthy,
-    /// Check failed
-    Unhealthy,
+    /// Check passed but the dependency is impaired
+    Degraded,
     /// Check status unknown
     Unknown,
+    /// Check failed
+    Unhealthy,
+}
+
+impl CheckStatus {
+    /// Lowercase name, as serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Healthy => "healthy",
+            CheckStatus::Degraded => "degraded",
+            CheckStatus::Unhealthy => "unhealthy",
+            CheckStatus::Unknown => "unknown",
+        }
+    }
+}
+
+/// Unit used when serializing check durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationUnit {
+    /// Integer milliseconds in `duration_ms`
+    #[default]
+    Milliseconds,
+    /// Integer microseconds in `duration_us`
+    Microseconds,
+    /// Fractional seconds in `duration_s`
+    Seconds,
 }
 
 /// Individual check result
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CheckResult {
     /// Status of this check
     pub status: CheckStatus,
@@ -27,43 +66,70 @@ pub struct CheckResult {
     /// Check duration in milliseconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_ms: Option<u64>,
+    /// Check duration in microseconds (with [`DurationUnit::Microseconds`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_us: Option<u64>,
+    /// Check duration in seconds (with [`DurationUnit::Seconds`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_s: Option<f64>,
+    /// Full-precision measured duration, converted on serialization
+    #[serde(skip)]
+    pub elapsed: Option<Duration>,
+    /// Stable machine-readable failure code (e.g. `db.connect`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Structured diagnostic details
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub details: HashMap<String, serde_json::Value>,
+    /// Whether the check can fail the overall status; optional checks
+    /// contribute at most `degraded`. Set when added to a [`HealthResponse`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    /// When the check last failed, reported even while it is passing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure_at: Option<DateTime<Utc>>,
 }
 
 impl CheckResult {
-    /// Create a healthy check result
-    pub fn healthy() -> Self {
+    fn with_status(status: CheckStatus, message: Option<String>) -> Self {
         Self {
-            status: CheckStatus::Healthy,
-            message: None,
+            status,
+            message,
             duration_ms: None,
+            duration_us: None,
+            duration_s: None,
+            elapsed: None,
+            code: None,
+            details: HashMap::new(),
+            required: None,
+            last_failure_at: None,
         }
     }
 
+    /// Create a healthy check result
+    pub fn healthy() -> Self {
+        Self::with_status(CheckStatus::Healthy, None)
+    }
+
     /// Create a healthy check result with a message
     pub fn healthy_with_message(message: impl Into<String>) -> Self {
-        Self {
-            status: CheckStatus::Healthy,
-            message: Some(message.into()),
-            duration_ms: None,
-        }
+        Self::with_status(CheckStatus::Healthy, Some(message.into()))
+    }
+
+    /// Create a degraded check result
+    pub fn degraded(message: impl Into<String>) -> Self {
+        Self::with_status(CheckStatus::Degraded, Some(message.into()))
     }
 
     /// Create an unhealthy check result
     pub fn unhealthy(message: impl Into<String>) -> Self {
-        Self {
-            status: CheckStatus::Unhealthy,
-            message: Some(message.into()),
-            duration_ms: None,
-        }
+        Self::with_status(CheckStatus::Unhealthy, Some(message.into()))
     }
 
     /// Create an unknown status check result
     pub fn unknown(message: impl Into<String>) -> Self {
-        Self {
-            status: CheckStatus::Unknown,
-            message: Some(message.into()),
-            duration_ms: None,
-        }
+        Self::with_status(CheckStatus::Unknown, Some(message.into()))
     }
 
     /// Set duration
@@ -71,33 +137,289 @@ impl CheckResult {
         self.duration_ms = Some(duration_ms);
         self
     }
+
+    /// Set duration from a full-precision measurement
+    ///
+    /// Also sets `duration_ms`, rounded down.
+    pub fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.duration_ms = Some(elapsed.as_millis() as u64);
+        self.elapsed = Some(elapsed);
+        self
+    }
+
+    /// Check duration at the best available precision
+    pub fn duration(&self) -> Option<Duration> {
+        self.elapsed
+            .or_else(|| self.duration_us.map(Duration::from_micros))
+            .or_else(|| self.duration_ms.map(Duration::from_millis))
+            .or_else(|| self.duration_s.and_then(|secs| Duration::try_from_secs_f64(secs).ok()))
+    }
+
+    /// Express the duration in `unit`
+    ///
+    /// Fills the field matching `unit` and clears the others. Uses the
+    /// full-precision `elapsed` when set, falling back to `duration_ms`.
+    pub fn with_duration_unit(mut self, unit: DurationUnit) -> Self {
+        let elapsed = self.duration();
+
+        self.duration_ms = None;
+        self.duration_us = None;
+        self.duration_s = None;
+
+        if let Some(elapsed) = elapsed {
+            match unit {
+                DurationUnit::Milliseconds => self.duration_ms = Some(elapsed.as_millis() as u64),
+                DurationUnit::Microseconds => self.duration_us = Some(elapsed.as_micros() as u64),
+                DurationUnit::Seconds => self.duration_s = Some(elapsed.as_secs_f64()),
+            }
+        }
+
+        self
+    }
+
+    /// Set a machine-readable code
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Add a diagnostic detail
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.details.insert(key.into(), value.into());
+        self
+    }
+
+    /// Record the `source()` chain of `error` in a `causes` detail
+    ///
+    /// Top-level error messages often hide the underlying reason, such as the
+    /// OS connection error or TLS failure. Nothing is added when `error` has
+    /// no source.
+    pub fn with_causes(self, error: &dyn std::error::Error) -> Self {
+        let causes: Vec<String> = std::iter::successors(error.source(), |cause| cause.source())
+            .map(|cause| cause.to_string())
+            .collect();
+
+        if causes.is_empty() {
+            self
+        } else {
+            self.with_detail("causes", causes)
+        }
+    }
+}
+
+/// Serialization format for the response timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+    /// RFC 3339 string (e.g. `2024-01-01T00:00:00Z`)
+    #[default]
+    Rfc3339,
+    /// Unix epoch milliseconds as an integer
+    EpochMillis,
+}
+
+/// Response timestamp with a runtime-selected serialization format
+///
+/// Deserializes from either an RFC 3339 string or epoch milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    /// Point in time
+    pub value: DateTime<Utc>,
+    /// Format used when serializing
+    pub format: TimestampFormat,
+}
+
+impl Timestamp {
+    /// Current time in the default format
+    pub fn now() -> Self {
+        Utc::now().into()
+    }
+
+    /// Set the serialization format
+    pub fn with_format(mut self, format: TimestampFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self {
+            value,
+            format: TimestampFormat::default(),
+        }
+    }
+}
+
+impl Deref for Timestamp {
+    type Target = DateTime<Utc>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.format {
+            TimestampFormat::Rfc3339 => self.value.serialize(serializer),
+            TimestampFormat::EpochMillis => serializer.serialize_i64(self.value.timestamp_millis()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Millis(i64),
+            Rfc3339(DateTime<Utc>),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Millis(millis) => Utc
+                .timestamp_millis_opt(millis)
+                .single()
+                .map(|value| Timestamp::from(value).with_format(TimestampFormat::EpochMillis))
+                .ok_or_else(|| serde::de::Error::custom("timestamp out of range")),
+            Raw::Rfc3339(value) => Ok(value.into()),
+        }
+    }
+}
+
+/// Rollup of check results by status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HealthSummary {
+    /// Total number of checks
+    pub total: usize,
+    /// Number of healthy checks
+    pub healthy: usize,
+    /// Number of degraded checks
+    pub degraded: usize,
+    /// Number of unhealthy checks
+    pub unhealthy: usize,
+    /// Number of checks with unknown status
+    pub unknown: usize,
+}
+
+impl HealthSummary {
+    /// Count check results by status
+    pub fn from_checks<'a>(checks: impl IntoIterator<Item = &'a CheckResult>) -> Self {
+        checks.into_iter().fold(Self::default(), |mut summary, result| {
+            summary.total += 1;
+            match result.status {
+                CheckStatus::Healthy => summary.healthy += 1,
+                CheckStatus::Degraded => summary.degraded += 1,
+                CheckStatus::Unhealthy => summary.unhealthy += 1,
+                CheckStatus::Unknown => summary.unknown += 1,
+            }
+            summary
+        })
+    }
+}
+
+/// Build metadata for correlating a running binary with a deploy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BuildInfo {
+    /// Git commit hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Build timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_timestamp: Option<String>,
+    /// Rust compiler version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rustc_version: Option<String>,
+}
+
+/// Capture [`BuildInfo`] from `vergen`-style compile-time environment variables
+///
+/// Reads `VERGEN_GIT_SHA`, `VERGEN_BUILD_TIMESTAMP` and `VERGEN_RUSTC_SEMVER`
+/// when compiling the calling crate. Variables that are not set are left as
+/// `None`.
+///
+/// ```rust
+/// use pleme_health::{build_info, HealthCheckBuilder};
+///
+/// let health = HealthCheckBuilder::new("my-service", "1.0.0")
+///     .with_build_info(build_info!())
+///     .build();
+/// ```
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::response::BuildInfo {
+            commit: ::std::option_env!("VERGEN_GIT_SHA").map(::std::string::String::from),
+            build_timestamp: ::std::option_env!("VERGEN_BUILD_TIMESTAMP")
+                .map(::std::string::String::from),
+            rustc_version: ::std::option_env!("VERGEN_RUSTC_SEMVER")
+                .map(::std::string::String::from),
+        }
+    };
 }
 
 /// Complete health check response
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct HealthResponse {
     /// Overall status (healthy if all checks pass)
     pub status: CheckStatus,
     /// Service name
     pub service: String,
-    /// Individual check results
-    pub checks: HashMap<String, CheckResult>,
-    /// Response timestamp
-    pub timestamp: DateTime<Utc>,
+    /// Individual check results, in registration order
+    pub checks: IndexMap<String, CheckResult>,
+    /// Response timestamp (RFC 3339 string or epoch milliseconds)
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = DateTime))]
+    pub timestamp: Timestamp,
     /// Service version
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// Deployment environment (e.g. `staging`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Identity of the answering instance (e.g. pod name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    /// Build metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_info: Option<BuildInfo>,
+    /// Check counts by status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<HealthSummary>,
+    /// Wall-clock time of the whole evaluation in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_duration_ms: Option<u64>,
+    /// Name of the check with the longest reported duration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slowest_check: Option<String>,
+    /// Why the checks were not (all) evaluated or the status was held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 impl HealthResponse {
     /// Create a new health response
     pub fn new(service: impl Into<String>) -> Self {
+        Self::new_with_clock(service, &SystemClock)
+    }
+
+    /// Create a new health response timestamped by `clock`
+    pub fn new_with_clock(service: impl Into<String>, clock: &dyn Clock) -> Self {
         Self {
             status: CheckStatus::Healthy,
             service: service.into(),
-            checks: HashMap::new(),
-            timestamp: Utc::now(),
+            checks: IndexMap::new(),
+            timestamp: clock.now().into(),
             version: None,
+            environment: None,
+            instance_id: None,
+            build_info: None,
+            summary: None,
+            total_duration_ms: None,
+            slowest_check: None,
+            reason: None,
         }
     }
 
@@ -107,16 +429,113 @@ impl HealthResponse {
         self
     }
 
+    /// Set the deployment environment
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Set the instance identity
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Set build metadata
+    pub fn with_build_info(mut self, build_info: BuildInfo) -> Self {
+        self.build_info = Some(build_info);
+        self
+    }
+
+    /// Set the timestamp serialization format
+    pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp.format = format;
+        self
+    }
+
+    /// Set the timestamp, keeping its serialization format
+    pub fn with_timestamp(mut self, at: DateTime<Utc>) -> Self {
+        self.timestamp.value = at;
+        self
+    }
+
     /// Add a check result
     pub fn add_check(mut self, name: impl Into<String>, result: CheckResult) -> Self {
-        let name = name.into();
+        // The overall status is the most severe of the check statuses
+        self.status = self.status.max(result.status);
+        let result = CheckResult {
+            required: Some(true),
+            ..result
+        };
+        self.checks.insert(name.into(), result);
+        self
+    }
 
-        // Update overall status if this check is unhealthy
-        if result.status == CheckStatus::Unhealthy {
-            self.status = CheckStatus::Unhealthy;
+    /// Add the result of an optional check
+    ///
+    /// An optional check is reported as-is but contributes at most
+    /// `Degraded` to the overall status.
+    pub fn add_optional_check(mut self, name: impl Into<String>, result: CheckResult) -> Self {
+        self.status = self.status.max(result.status.min(CheckStatus::Degraded));
+        let result = CheckResult {
+            required: Some(false),
+            ..result
+        };
+        self.checks.insert(name.into(), result);
+        self
+    }
+
+    /// Set the reason the response was produced without a full evaluation
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Mark the response unhealthy without running the named checks
+    ///
+    /// Sets `reason` and records an `unknown` placeholder for each check not
+    /// already present, so short-circuited responses still list every check
+    /// that would have run. Each name comes with whether the check is
+    /// required, which the placeholder reports in `required`.
+    pub fn short_circuit<I, S>(mut self, reason: impl Into<String>, checks: I) -> Self
+    where
+        I: IntoIterator<Item = (S, bool)>,
+        S: Into<String>,
+    {
+        let reason = reason.into();
+
+        for (name, required) in checks {
+            self.checks.entry(name.into()).or_insert_with(|| CheckResult {
+                required: Some(required),
+                ..CheckResult::unknown(format!("not evaluated: {}", reason))
+            });
         }
 
-        self.checks.insert(name, result);
+        self.status = CheckStatus::Unhealthy;
+        self.reason = Some(reason);
+        self
+    }
+
+    /// Compute the summary counts from the current check results
+    ///
+    /// Call after all checks have been added so the counts match `checks`.
+    pub fn with_summary(mut self) -> Self {
+        self.summary = Some(HealthSummary::from_checks(self.checks.values()));
+        self
+    }
+
+    /// Record the total evaluation time and the slowest check
+    ///
+    /// The slowest check is derived from the per-check durations, so call
+    /// after all checks have been added.
+    pub fn with_timing(mut self, total_duration_ms: u64) -> Self {
+        self.total_duration_ms = Some(total_duration_ms);
+        self.slowest_check = self
+            .checks
+            .iter()
+            .filter_map(|(name, result)| result.duration().map(|duration| (name, duration)))
+            .max_by_key(|(_, duration)| *duration)
+            .map(|(name, _)| name.clone());
         self
     }
 
@@ -124,4 +543,75 @@ impl HealthResponse {
     pub fn is_healthy(&self) -> bool {
         self.status == CheckStatus::Healthy
     }
+
+    /// Whether the readiness probe passes (healthy or degraded)
+    pub fn is_ready(&self) -> bool {
+        matches!(self.status, CheckStatus::Healthy | CheckStatus::Degraded)
+    }
+
+    /// Whether some checks are healthy while others are not
+    pub fn is_mixed(&self) -> bool {
+        let mut statuses = self.checks.values().map(|result| result.status);
+        let healthy = statuses.clone().any(|status| status == CheckStatus::Healthy);
+        healthy && statuses.any(|status| status != CheckStatus::Healthy)
+    }
+
+    /// HTTP status code for this response on the readiness probe
+    ///
+    /// Healthy and degraded map to 200 so the instance stays in rotation;
+    /// unhealthy and unknown map to 503.
+    #[cfg(feature = "axum")]
+    pub fn status_code(&self) -> StatusCode {
+        if self.is_ready() {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    }
+
+    /// Render the check results as InfluxDB line protocol
+    ///
+    /// One `health_check` line per check, tagged with `service`, `check` and
+    /// `status`, with an `up` field (1 for healthy or degraded, 0 otherwise),
+    /// `duration_ms` when known, and the response timestamp in nanoseconds.
+    pub fn to_line_protocol(&self) -> String {
+        let timestamp = self.timestamp.timestamp_nanos_opt().unwrap_or_default();
+
+        self.checks
+            .iter()
+            .map(|(name, result)| {
+                let up = matches!(result.status, CheckStatus::Healthy | CheckStatus::Degraded);
+                let mut fields = format!("up={}i", u8::from(up));
+
+                if let Some(duration) = result.duration() {
+                    fields.push_str(&format!(",duration_ms={}", duration.as_secs_f64() * 1000.0));
+                }
+
+                format!(
+                    "health_check,service={},check={},status={} {} {}\n",
+                    escape_tag(&self.service),
+                    escape_tag(name),
+                    result.status.as_str(),
+                    fields,
+                    timestamp
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "axum")]
+impl From<&HealthResponse> for StatusCode {
+    fn from(response: &HealthResponse) -> Self {
+        response.status_code()
+    }
+}
+
+/// Escape a line protocol tag key or value
+fn escape_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
 }
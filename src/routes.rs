@@ -1,91 +1,967 @@
-//! Axum route integration for health checks
-
-use crate::checks::HealthCheck;
-use crate::response::{HealthResponse, CheckStatus};
-use axum::{
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::get,
-    Json, Router,
+//! Health check evaluation and Axum route integration
+//!
+//! The routes, handlers and HTTP-only options require the `axum` feature;
+//! evaluating checks through [`HealthRoutes::check`] does not.
+
+use crate::checks::{
+    run_check_with_clock, CheckMap, CheckSet, CheckState, HealthCheck, Probe, RegisteredCheck,
 };
+use crate::clock::Clock;
+use crate::response::{BuildInfo, CheckResult, CheckStatus, DurationUnit, HealthResponse, TimestampFormat};
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, Shared};
+use futures::stream::{FuturesUnordered, Stream};
+use futures::FutureExt;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+#[cfg(feature = "axum")]
+pub(crate) mod http;
+
+/// How often [`HealthRoutes::wait_until_ready`] re-evaluates the checks
+const READY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[cfg(feature = "axum")]
+pub use http::health_routes;
+#[cfg(feature = "axum")]
+pub(crate) use http::PlainTextBodies;
+
+/// A check execution that concurrent callers can await together, tagged
+/// with its execution id
+pub(crate) type SharedCheck = Shared<BoxFuture<'static, (u64, CheckResult)>>;
+
+/// Source of execution ids; later executions get larger ids
+static NEXT_EXECUTION: AtomicU64 = AtomicU64::new(1);
+
+/// How long a readiness change must persist before it is reported
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Hysteresis {
+    pub(crate) unready_after: Duration,
+    pub(crate) ready_after: Duration,
+}
+
+/// Reported aggregate readiness, tracked for hysteresis
+#[derive(Debug, Default)]
+pub(crate) struct ReadinessState {
+    reported_ready: bool,
+    pending_since: Option<Instant>,
+}
+
+/// Callback invoked with `(was_ready, is_ready, at)` when readiness flips
+pub(crate) type ReadinessCallback = Arc<dyn Fn(bool, bool, DateTime<Utc>) + Send + Sync>;
 
-/// Health routes for Axum integration
+/// Health check evaluation, served over Axum with the `axum` feature
 #[derive(Clone)]
 pub struct HealthRoutes {
     pub(crate) service_name: Arc<String>,
     pub(crate) version: Option<Arc<String>>,
-    pub(crate) checks: Arc<HashMap<String, HealthCheck>>,
+    pub(crate) environment: Option<Arc<String>>,
+    pub(crate) instance_id: Option<Arc<String>>,
+    pub(crate) build_info: Option<Arc<BuildInfo>>,
+    pub(crate) checks: Arc<CheckSet>,
+    pub(crate) timestamp_format: TimestampFormat,
+    pub(crate) include_summary: bool,
+    pub(crate) include_timing: bool,
+    pub(crate) clock: Arc<dyn Clock>,
+    #[cfg(feature = "axum")]
+    pub(crate) compression_threshold: Option<u16>,
+    #[cfg(feature = "axum")]
+    pub(crate) cors_origins: Option<Arc<Vec<axum::http::HeaderValue>>>,
+    pub(crate) duration_unit: DurationUnit,
+    pub(crate) fail_fast: bool,
+    pub(crate) require_checks: bool,
+    pub(crate) check_state: Arc<Mutex<HashMap<(Probe, String), CheckState>>>,
+    pub(crate) maintenance: Arc<AtomicBool>,
+    pub(crate) inflight: Arc<Mutex<HashMap<String, SharedCheck>>>,
+    #[cfg(feature = "axum")]
+    pub(crate) maintenance_token: Option<Arc<String>>,
+    #[cfg(feature = "axum")]
+    pub(crate) mixed_status: Option<axum::http::StatusCode>,
+    pub(crate) warmup_until: Option<Instant>,
+    pub(crate) optional_failure_threshold: Option<usize>,
+    pub(crate) readiness_callback: Option<ReadinessCallback>,
+    pub(crate) default_timeout: Option<Duration>,
+    pub(crate) hysteresis: Option<Hysteresis>,
+    pub(crate) readiness_state: Arc<Mutex<ReadinessState>>,
+    #[cfg(feature = "axum")]
+    pub(crate) plain_text: Option<Arc<PlainTextBodies>>,
+    #[cfg(feature = "prometheus")]
+    pub(crate) metrics: crate::metrics::CheckMetrics,
+    #[cfg(feature = "axum")]
+    pub(crate) probe_logger: Option<Arc<crate::logging::ProbeLogger>>,
+    pub(crate) updates: broadcast::Sender<HealthResponse>,
+    pub(crate) latest: Arc<Mutex<Option<HealthResponse>>>,
 }
 
 impl HealthRoutes {
-    /// Create Axum routes for health endpoints
+    /// Register per-check metrics into an existing Prometheus registry
     ///
-    /// Adds:
-    /// - `GET /health` - Liveness probe (always returns 200)
-    /// - `GET /ready` - Readiness probe (200 if healthy, 503 if not)
-    pub fn routes(&self) -> Router {
-        let health_handler = self.clone();
-        let ready_handler = self.clone();
+    /// Adds `health_check_status{check, status}` (1 for the current status,
+    /// 0 otherwise) and `health_check_duration_seconds{check}`. They are
+    /// updated whenever checks run, so pair with
+    /// [`spawn_background_checks`](Self::spawn_background_checks) to keep
+    /// them current between probes.
+    #[cfg(feature = "prometheus")]
+    pub fn register_metrics(&self, registry: &mut prometheus_client::registry::Registry) {
+        self.metrics.register(registry);
+    }
+
+    /// Run the readiness checks periodically on a background task
+    ///
+    /// Each evaluation refreshes the latest result and notifies
+    /// `/health/stream` subscribers when the aggregate status changes.
+    /// Abort the returned handle to stop the runner.
+    pub fn spawn_background_checks(&self, interval: Duration) -> JoinHandle<()> {
+        let routes = self.clone();
 
-        Router::new()
-            .route("/health", get(move || health_endpoint(health_handler)))
-            .route("/ready", get(move || readiness_endpoint(ready_handler)))
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                routes.evaluate().await;
+            }
+        })
     }
-}
 
-/// Health endpoint handler (liveness probe)
-///
-/// Always returns 200 OK with basic service info
-async fn health_endpoint(routes: HealthRoutes) -> Json<HealthResponse> {
-    let mut response = HealthResponse::new(routes.service_name.as_str());
+    /// Wait until the readiness checks first report ready
+    ///
+    /// Evaluates like `GET /ready`, re-checking every second and whenever the
+    /// background runner reports a status change, e.g. to hold back a job
+    /// scheduler until its dependencies are up. Wrap in
+    /// `tokio::time::timeout` to bound the wait.
+    pub async fn wait_until_ready(&self) {
+        let mut updates = self.updates.subscribe();
 
-    if let Some(version) = &routes.version {
-        response = response.with_version(version.as_str());
+        loop {
+            if self.evaluate().await.is_ready() {
+                return;
+            }
+
+            tokio::select! {
+                update = updates.recv() => {
+                    if update.is_ok_and(|response| response.is_ready()) {
+                        return;
+                    }
+                }
+                _ = tokio::time::sleep(READY_POLL_INTERVAL) => {}
+            }
+        }
     }
 
-    Json(response)
-}
+    /// Enable or disable maintenance mode
+    ///
+    /// While enabled, `/ready` returns 503 with `"reason": "maintenance"`
+    /// without running any checks, taking the instance out of rotation.
+    /// Liveness is unaffected.
+    pub fn set_maintenance(&self, enabled: bool) {
+        self.maintenance.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether maintenance mode is enabled
+    pub fn is_maintenance(&self) -> bool {
+        self.maintenance.load(Ordering::Relaxed)
+    }
+
+    /// Run the readiness checks outside of HTTP
+    ///
+    /// Evaluates exactly what `GET /ready` does, e.g. to gate application
+    /// startup on dependencies or to reuse the checks from a CLI.
+    pub async fn check(&self) -> HealthResponse {
+        self.evaluate().await
+    }
+
+    /// Run the readiness checks and render them as InfluxDB line protocol
+    ///
+    /// Evaluates like `GET /ready`; see [`HealthResponse::to_line_protocol`].
+    pub async fn line_protocol(&self) -> String {
+        self.evaluate().await.to_line_protocol()
+    }
+
+    /// Run a single registered check by name
+    ///
+    /// Returns `None` if no check has that name or it is currently toggled
+    /// off.
+    pub async fn check_one(&self, name: &str) -> Option<CheckResult> {
+        let registered = self.checks.load().get(name)?.clone();
+
+        if !registered.is_enabled() {
+            return None;
+        }
+
+        Some(self.run_registered(name, &registered).await.1)
+    }
+
+    /// Run the readiness checks concurrently, yielding each result as it completes
+    ///
+    /// For incremental UIs: a slow check doesn't hold back the fast ones.
+    /// Failure policies apply as in [`check`](Self::check), but results are
+    /// per check only; maintenance, warm-up and the status stream are not
+    /// involved. Dropping the stream cancels the checks still running.
+    pub fn check_stream(&self) -> impl Stream<Item = (String, CheckResult)> + '_ {
+        self.checks
+            .load()
+            .iter()
+            .filter(|(_, registered)| registered.is_enabled() && registered.probe.readiness())
+            .map(|(name, registered)| {
+                let name = name.clone();
+                let registered = registered.clone();
+
+                async move {
+                    let (execution, result) = self.run_registered(&name, &registered).await;
+                    let result = self.apply_failure_policy(
+                        Probe::Readiness,
+                        &name,
+                        &registered,
+                        execution,
+                        result,
+                    );
+
+                    #[cfg(feature = "prometheus")]
+                    self.metrics.observe(&name, &result);
+
+                    (name, result)
+                }
+            })
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    /// Add or replace a check at runtime
+    ///
+    /// The builder's default check timeout applies. Probes already running
+    /// keep the set they started with. A replaced check's failure history
+    /// is discarded.
+    pub fn insert_check(&self, name: impl Into<String>, check: HealthCheck) {
+        let name = name.into();
+        let registered = self.register(check);
+
+        self.checks.update(|checks| {
+            let mut checks = checks.clone();
+            checks.insert(name.clone(), registered.clone());
+            checks
+        });
+        self.forget_state(&name);
+    }
+
+    /// Remove a check at runtime, returning whether it was registered
+    pub fn remove_check(&self, name: &str) -> bool {
+        let registered = self.checks.load().contains_key(name);
+
+        self.checks.update(|checks| {
+            let mut checks = checks.clone();
+            checks.shift_remove(name);
+            checks
+        });
+        self.forget_state(name);
+
+        registered
+    }
+
+    /// Drop the failure streaks recorded for `name` on every probe
+    fn forget_state(&self, name: &str) {
+        self.check_state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(_, state_name), _| state_name != name);
+    }
+
+    /// Atomically replace all checks at runtime
+    ///
+    /// Like [`insert_check`](Self::insert_check), the failure history of
+    /// every replaced check is discarded.
+    pub fn replace_checks<I, S>(&self, checks: I)
+    where
+        I: IntoIterator<Item = (S, HealthCheck)>,
+        S: Into<String>,
+    {
+        let replacement: CheckMap = checks
+            .into_iter()
+            .map(|(name, check)| (name.into(), self.register(check)))
+            .collect();
+
+        self.checks.update(|_| replacement.clone());
+        self.check_state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Register a check added at runtime with default options
+    fn register(&self, check: HealthCheck) -> Arc<RegisteredCheck> {
+        Arc::new(RegisteredCheck::new(check).with_timeout_applied(self.default_timeout))
+    }
+
+    /// Run all enabled readiness checks and record the result
+    async fn evaluate(&self) -> HealthResponse {
+        let start = self.clock.instant();
+        // Read once so the branch and the hysteresis exemption agree
+        let short_circuit = if self.is_maintenance() {
+            Some("maintenance")
+        } else if self.is_warming_up() {
+            Some("warming up")
+        } else {
+            None
+        };
+        let mut response = match short_circuit {
+            Some(reason) => self
+                .base_response()
+                .short_circuit(reason, self.readiness_checks()),
+            None => {
+                self.run_checks(self.base_response(), Probe::Readiness)
+                    .await
+            }
+        };
+
+        if self.require_checks && response.checks.is_empty() && response.reason.is_none() {
+            response.status = CheckStatus::Unknown;
+            response = response.with_reason("no checks evaluated");
+        }
+
+        // Maintenance and warmup are deliberate, so they bypass hysteresis
+        if let (Some(hysteresis), None) = (self.hysteresis, short_circuit) {
+            response = self.apply_hysteresis(hysteresis, response);
+        }
+
+        // Stamp with the completion time so slow checks don't leave it stale
+        response = response.with_timestamp(self.clock.now());
+
+        if self.include_summary {
+            response = response.with_summary();
+        }
+
+        if self.include_timing {
+            let elapsed = self.clock.instant().duration_since(start);
+            response = response.with_timing(elapsed.as_millis() as u64);
+        }
+
+        self.record(&response);
+        response
+    }
+
+    /// Whether the warmup period is still running
+    fn is_warming_up(&self) -> bool {
+        self.warmup_until
+            .is_some_and(|until| self.clock.instant() < until)
+    }
+
+    /// Names of the enabled readiness checks and whether each is critical
+    fn readiness_checks(&self) -> Vec<(String, bool)> {
+        self.checks
+            .load()
+            .iter()
+            .filter(|(_, registered)| registered.is_enabled() && registered.probe.readiness())
+            .map(|(name, registered)| (name.clone(), registered.critical))
+            .collect()
+    }
+
+    /// Run all enabled liveness checks
+    #[cfg(feature = "axum")]
+    async fn evaluate_liveness(&self) -> HealthResponse {
+        self.run_checks(self.base_response(), Probe::Liveness).await
+    }
+
+    /// Run the enabled checks registered for `probe` (liveness or readiness)
+    /// into `response`
+    async fn run_checks(&self, mut response: HealthResponse, probe: Probe) -> HealthResponse {
+        let runs_on = |registered: &RegisteredCheck| match probe {
+            Probe::Liveness => registered.probe.liveness(),
+            Probe::Readiness | Probe::Both => registered.probe.readiness(),
+        };
+        let checks = self.checks.load();
+        let mut selected = checks
+            .iter()
+            .filter(|(_, registered)| registered.is_enabled() && runs_on(registered));
+
+        let mut optional_failures = 0;
+
+        while let Some((name, registered)) = selected.next() {
+            let (execution, result) = self.run_registered(name, registered).await;
+            let result = self.apply_failure_policy(probe, name, registered, execution, result);
+            let failed = result.status == CheckStatus::Unhealthy;
+
+            #[cfg(feature = "prometheus")]
+            self.metrics.observe(name, &result);
+
+            if registered.critical {
+                response = response.add_check(name, result);
+            } else if result.status != CheckStatus::Healthy
+                && self.optional_failure_threshold.is_some()
+            {
+                // Tolerated individually; the threshold is applied below
+                optional_failures += 1;
+                let result = CheckResult {
+                    required: Some(false),
+                    ..result
+                };
+                response.checks.insert(name.clone(), result);
+            } else {
+                response = response.add_optional_check(name, result);
+            }
+
+            if self.fail_fast && failed && registered.critical {
+                let skipped =
+                    selected.map(|(name, registered)| (name.as_str(), registered.critical));
+                return response.short_circuit("fail-fast", skipped);
+            }
+        }
+
+        let exceeded = self
+            .optional_failure_threshold
+            .is_some_and(|threshold| optional_failures > threshold);
+
+        if exceeded {
+            response.status = response.status.max(CheckStatus::Degraded);
+        }
+
+        response
+    }
+
+    /// Run one check, expressing its duration in the configured unit
+    ///
+    /// Checks that don't report their own duration (most failure paths) are
+    /// timed with the configured clock, so every result carries one.
+    /// Returns the execution id alongside the result.
+    async fn run_registered(
+        &self,
+        name: &str,
+        registered: &Arc<RegisteredCheck>,
+    ) -> (u64, CheckResult) {
+        let start = self.clock.instant();
+        let (execution, mut result) = self.run_shared(name, registered).await;
+
+        if result.duration().is_none() {
+            result = result.with_elapsed(self.clock.instant().saturating_duration_since(start));
+        }
+
+        if self.duration_unit != DurationUnit::Milliseconds {
+            result = result.with_duration_unit(self.duration_unit);
+        }
+
+        (execution, result)
+    }
+
+    /// Run a check, joining an execution of the same check already in flight
+    ///
+    /// Concurrent callers (e.g. the background runner and a probe) share a
+    /// single execution instead of each hitting the dependency; they all
+    /// receive its execution id.
+    async fn run_shared(
+        &self,
+        name: &str,
+        registered: &Arc<RegisteredCheck>,
+    ) -> (u64, CheckResult) {
+        let execution = {
+            let mut inflight = self.inflight.lock().unwrap_or_else(PoisonError::into_inner);
+
+            match inflight.get(name) {
+                Some(execution) => execution.clone(),
+                None => {
+                    let registered = registered.clone();
+                    let clock = self.clock.clone();
+                    let id = NEXT_EXECUTION.fetch_add(1, Ordering::Relaxed);
+                    let execution =
+                        async move { (id, run_check_with_clock(&registered.check, clock).await) }
+                            .boxed()
+                            .shared();
+
+                    inflight.insert(name.to_string(), execution.clone());
+                    execution
+                }
+            }
+        };
+
+        let result = execution.clone().await;
+
+        let mut inflight = self.inflight.lock().unwrap_or_else(PoisonError::into_inner);
+        if inflight
+            .get(name)
+            .is_some_and(|current| current.ptr_eq(&execution))
+        {
+            inflight.remove(name);
+        }
+
+        result
+    }
+
+    /// Track the check's failure streak and soften failures below its threshold
+    ///
+    /// Streaks are tracked per probe, and each execution counts once however
+    /// many callers joined it. Also stamps the result with the time of the
+    /// check's most recent failure.
+    fn apply_failure_policy(
+        &self,
+        probe: Probe,
+        name: &str,
+        registered: &RegisteredCheck,
+        execution: u64,
+        mut result: CheckResult,
+    ) -> CheckResult {
+        let mut states = self.check_state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = states.entry((probe, name.to_string())).or_default();
+        let first_seen = execution > state.last_execution;
+
+        if first_seen {
+            state.last_execution = execution;
+        }
+
+        if result.status != CheckStatus::Unhealthy {
+            if first_seen {
+                state.consecutive_failures = 0;
+            }
+            result.last_failure_at = state.last_failure_at;
+            return result;
+        }
+
+        if first_seen {
+            state.last_failure_at = Some(self.clock.now());
+            state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        }
+        result.last_failure_at = state.last_failure_at;
+
+        // A result older than the state's latest execution counts as one failure
+        let failures = state.consecutive_failures.max(1);
+        let threshold = registered.failure_policy.consecutive_failures;
+
+        if failures >= threshold {
+            return result;
+        }
+
+        let message = format!(
+            "Failure {} of {} tolerated: {}",
+            failures,
+            threshold,
+            result.message.as_deref().unwrap_or("check failed")
+        );
+
+        CheckResult {
+            status: CheckStatus::Degraded,
+            message: Some(message),
+            ..result
+        }
+        .with_detail("consecutive_failures", failures)
+    }
+
+    /// Hold the reported readiness until a change has persisted long enough
+    ///
+    /// A ready service stays ready (as degraded) through failures shorter
+    /// than `unready_after`; an unready one stays unready (as unknown) until
+    /// it has been passing for `ready_after`.
+    fn apply_hysteresis(
+        &self,
+        hysteresis: Hysteresis,
+        mut response: HealthResponse,
+    ) -> HealthResponse {
+        let mut state = self.readiness_state.lock().unwrap_or_else(PoisonError::into_inner);
+        let ready = response.is_ready();
+
+        if ready == state.reported_ready {
+            state.pending_since = None;
+            return response;
+        }
+
+        let now = self.clock.instant();
+        let since = *state.pending_since.get_or_insert(now);
+        let required = if ready {
+            hysteresis.ready_after
+        } else {
+            hysteresis.unready_after
+        };
+
+        if now.duration_since(since) >= required {
+            state.reported_ready = ready;
+            state.pending_since = None;
+            return response;
+        }
 
-/// Readiness endpoint handler (readiness probe)
-///
-/// Returns 200 OK if all checks pass, 503 Service Unavailable otherwise
-async fn readiness_endpoint(routes: HealthRoutes) -> Response {
-    let mut response = HealthResponse::new(routes.service_name.as_str());
+        if state.reported_ready {
+            response.status = CheckStatus::Degraded;
+            response.with_reason("failing within unready_after grace period")
+        } else {
+            response.status = CheckStatus::Unknown;
+            response.with_reason("passing for less than ready_after")
+        }
+    }
+
+    /// Store the latest evaluation and broadcast aggregate status changes
+    fn record(&self, response: &HealthResponse) {
+        let previous = self
+            .latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(response.clone());
+
+        if let (Some(callback), Some(previous)) = (&self.readiness_callback, &previous) {
+            if previous.is_ready() != response.is_ready() {
+                callback(previous.is_ready(), response.is_ready(), *response.timestamp);
+            }
+        }
 
-    if let Some(version) = &routes.version {
-        response = response.with_version(version.as_str());
+        if previous.map(|previous| previous.status) != Some(response.status) {
+            // Having no subscribers is not an error
+            let _ = self.updates.send(response.clone());
+        }
     }
 
-    // Run all health checks
-    for (name, check) in routes.checks.iter() {
-        let result = check().await;
-        response = response.add_check(name, result);
+    /// Most recent evaluation, if any
+    #[cfg(feature = "axum")]
+    fn latest_response(&self) -> Option<HealthResponse> {
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 
-    // Return appropriate status code
-    let status_code = if response.is_healthy() {
-        StatusCode::OK
-    } else {
-        StatusCode::SERVICE_UNAVAILABLE
-    };
+    /// Create an empty response carrying the service identity
+    fn base_response(&self) -> HealthResponse {
+        let mut response = HealthResponse::new_with_clock(self.service_name.as_str(), &*self.clock)
+            .with_timestamp_format(self.timestamp_format);
+
+        if let Some(version) = &self.version {
+            response = response.with_version(version.as_str());
+        }
+
+        if let Some(environment) = &self.environment {
+            response = response.with_environment(environment.as_str());
+        }
+
+        if let Some(instance_id) = &self.instance_id {
+            response = response.with_instance_id(instance_id.as_str());
+        }
 
-    (status_code, Json(response)).into_response()
+        if let Some(build_info) = &self.build_info {
+            response = response.with_build_info(BuildInfo::clone(build_info));
+        }
+
+        response
+    }
 }
 
-/// Standalone health check routes (simpler API)
-///
-/// Create health routes directly without builder
-pub fn health_routes(
-    service_name: impl Into<String>,
-    version: Option<String>,
-    checks: HashMap<String, HealthCheck>,
-) -> Router {
-    HealthRoutes {
-        service_name: Arc::new(service_name.into()),
-        version: version.map(Arc::new),
-        checks: Arc::new(checks),
-    }
-    .routes()
+#[cfg(test)]
+mod tests {
+    use crate::builder::{BuildError, HealthCheckBuilder};
+    use crate::checks::{all_of, FailurePolicy, HealthCheck, Probe, RegisteredCheck};
+    use crate::config::{CheckConfig, HealthConfig};
+    use crate::response::{CheckResult, CheckStatus, HealthResponse, TimestampFormat};
+    use crate::testing::{mock_check, MockClock};
+    use chrono::{TimeZone, Utc};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A failing check that takes `duration` on `clock` without reporting it
+    fn advancing_check(clock: &Arc<MockClock>, duration: Duration) -> HealthCheck {
+        let clock = clock.clone();
+        Box::new(move || {
+            let clock = clock.clone();
+            Box::pin(async move {
+                clock.advance(duration);
+                CheckResult::unhealthy("down")
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn slowest_check_includes_failures_without_a_duration() {
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check(
+                "fast",
+                mock_check(CheckResult::healthy().with_elapsed(Duration::from_millis(5))),
+            )
+            .add_check(
+                "slow",
+                Box::new(|| {
+                    Box::pin(async {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        CheckResult::unhealthy("connection refused")
+                    })
+                }),
+            )
+            .with_timing()
+            .build();
+
+        let response = health.check().await;
+
+        assert_eq!(response.slowest_check.as_deref(), Some("slow"));
+        assert!(response.checks["slow"].duration().unwrap() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn mock_clock_makes_timestamps_and_durations_exact() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let clock = Arc::new(MockClock::new(start));
+
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .with_clock(clock.clone())
+            .add_check("raw", advancing_check(&clock, Duration::from_millis(250)))
+            .add_check(
+                "combined",
+                all_of(vec![advancing_check(&clock, Duration::from_millis(100))]),
+            )
+            .with_timing()
+            .build();
+
+        let response = health.check().await;
+
+        assert_eq!(response.checks["raw"].duration_ms, Some(250));
+        assert_eq!(response.checks["combined"].duration_ms, Some(100));
+        assert_eq!(response.total_duration_ms, Some(350));
+        assert_eq!(*response.timestamp, start + Duration::from_millis(350));
+    }
+
+    #[test]
+    fn failure_streaks_are_tracked_per_probe() {
+        let health = HealthCheckBuilder::new("test", "1.0.0").build();
+        let mut registered = RegisteredCheck::new(mock_check(CheckResult::unhealthy("down")));
+        registered.probe = Probe::Both;
+        registered.failure_policy = FailurePolicy {
+            consecutive_failures: 2,
+        };
+
+        let fail = |probe, execution| {
+            let result = CheckResult::unhealthy("down");
+            health.apply_failure_policy(probe, "db", &registered, execution, result).status
+        };
+
+        assert_eq!(fail(Probe::Liveness, 1), CheckStatus::Degraded);
+        assert_eq!(fail(Probe::Readiness, 2), CheckStatus::Degraded);
+        assert_eq!(fail(Probe::Readiness, 3), CheckStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn joined_executions_advance_the_failure_streak_once() {
+        let executions = Arc::new(AtomicUsize::new(0));
+        let counter = executions.clone();
+        let check: HealthCheck = Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                CheckResult::unhealthy("down")
+            })
+        });
+
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check_with_policy(
+                "db",
+                check,
+                FailurePolicy {
+                    consecutive_failures: 2,
+                },
+            )
+            .build();
+
+        let (first, second) = tokio::join!(health.check(), health.check());
+
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+        assert_eq!(first.checks["db"].status, CheckStatus::Degraded);
+        assert_eq!(second.checks["db"].status, CheckStatus::Degraded);
+
+        let third = health.check().await;
+        assert_eq!(third.checks["db"].status, CheckStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn hysteresis_holds_readiness_through_fail_fast() {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let flag = healthy.clone();
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check(
+                "db",
+                Box::new(move || {
+                    let healthy = flag.load(Ordering::SeqCst);
+                    Box::pin(async move {
+                        if healthy {
+                            CheckResult::healthy()
+                        } else {
+                            CheckResult::unhealthy("down")
+                        }
+                    })
+                }),
+            )
+            .with_fail_fast()
+            .with_readiness_hysteresis(Duration::from_secs(3600), Duration::ZERO)
+            .build();
+
+        assert!(health.check().await.is_ready());
+
+        healthy.store(false, Ordering::SeqCst);
+        let response = health.check().await;
+
+        assert!(response.is_ready());
+        assert_eq!(response.status, CheckStatus::Degraded);
+        assert_eq!(
+            response.reason.as_deref(),
+            Some("failing within unready_after grace period")
+        );
+    }
+
+    #[tokio::test]
+    async fn optional_failure_threshold_counts_every_non_healthy_result() {
+        let build = |failing: Vec<CheckResult>| {
+            failing.into_iter().enumerate().fold(
+                HealthCheckBuilder::new("test", "1.0.0")
+                    .add_check("db", mock_check(CheckResult::healthy()))
+                    .degrade_when_optional_failures_exceed(2),
+                |builder, (i, result)| {
+                    builder.add_optional_check(format!("optional-{}", i), mock_check(result))
+                },
+            )
+            .build()
+        };
+
+        let tolerated = build(vec![
+            CheckResult::unknown("timed out"),
+            CheckResult::unhealthy("down"),
+        ]);
+        let response = tolerated.check().await;
+        assert_eq!(response.status, CheckStatus::Healthy);
+        assert_eq!(response.checks["optional-0"].status, CheckStatus::Unknown);
+
+        let exceeded = build(vec![
+            CheckResult::unknown("timed out"),
+            CheckResult::unhealthy("down"),
+            CheckResult::degraded("slow"),
+        ]);
+        assert_eq!(exceeded.check().await.status, CheckStatus::Degraded);
+    }
+
+    #[tokio::test]
+    async fn fail_fast_placeholders_report_whether_checks_are_required() {
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check("db", mock_check(CheckResult::unhealthy("down")))
+            .add_optional_check("cache", mock_check(CheckResult::healthy()))
+            .add_check("queue", mock_check(CheckResult::healthy()))
+            .with_fail_fast()
+            .build();
+
+        let response = health.check().await;
+
+        assert_eq!(response.reason.as_deref(), Some("fail-fast"));
+        assert_eq!(response.checks["cache"].status, CheckStatus::Unknown);
+        assert_eq!(response.checks["cache"].required, Some(false));
+        assert_eq!(response.checks["queue"].required, Some(true));
+    }
+
+    #[tokio::test]
+    async fn insert_check_discards_the_replaced_checks_failure_history() {
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check("db", mock_check(CheckResult::unhealthy("down")))
+            .build();
+
+        assert!(health.check().await.checks["db"].last_failure_at.is_some());
+
+        health.insert_check("db", mock_check(CheckResult::healthy()));
+        let response = health.check().await;
+
+        assert_eq!(response.checks["db"].status, CheckStatus::Healthy);
+        assert_eq!(response.checks["db"].last_failure_at, None);
+    }
+
+    #[test]
+    fn apply_config_rejects_unknown_checks() {
+        let mut config = HealthConfig::default();
+        config.checks.insert("missing".to_string(), CheckConfig::default());
+
+        let result = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check("db", mock_check(CheckResult::healthy()))
+            .apply_config(&config);
+
+        assert_eq!(result.err(), Some(BuildError::UnknownCheck("missing".to_string())));
+    }
+
+    #[tokio::test]
+    async fn apply_config_overrides_registered_checks() {
+        let config = HealthConfig {
+            default_timeout_ms: None,
+            checks: [
+                (
+                    "slow".to_string(),
+                    CheckConfig {
+                        timeout_ms: Some(20),
+                        ..CheckConfig::default()
+                    },
+                ),
+                (
+                    "search".to_string(),
+                    CheckConfig {
+                        critical: Some(false),
+                        ..CheckConfig::default()
+                    },
+                ),
+                (
+                    "billing".to_string(),
+                    CheckConfig {
+                        enabled: false,
+                        ..CheckConfig::default()
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let health = HealthCheckBuilder::new("test", "1.0.0")
+            .add_check(
+                "slow",
+                Box::new(|| {
+                    Box::pin(async {
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        CheckResult::healthy()
+                    })
+                }),
+            )
+            .add_check("search", mock_check(CheckResult::unhealthy("down")))
+            .add_check("billing", mock_check(CheckResult::healthy()))
+            .apply_config(&config)
+            .unwrap_or_else(|e| panic!("config rejected: {}", e))
+            .build();
+
+        let response = health.check().await;
+
+        assert_eq!(response.checks["slow"].code.as_deref(), Some("check.timeout"));
+        assert_eq!(response.checks["search"].required, Some(false));
+        assert!(!response.checks.contains_key("billing"));
+    }
+
+    #[tokio::test]
+    async fn timestamps_round_trip_in_both_formats() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        for (format, expected) in [
+            (TimestampFormat::Rfc3339, serde_json::json!("2024-01-01T00:00:00Z")),
+            (TimestampFormat::EpochMillis, serde_json::json!(start.timestamp_millis())),
+        ] {
+            let health = HealthCheckBuilder::new("test", "1.0.0")
+                .with_clock(Arc::new(MockClock::new(start)))
+                .with_timestamp_format(format)
+                .build();
+
+            let json = serde_json::to_value(health.check().await).unwrap();
+            assert_eq!(json["timestamp"], expected);
+
+            let parsed: HealthResponse = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed.timestamp.value, start);
+            assert_eq!(parsed.timestamp.format, format);
+        }
+    }
+
+    #[tokio::test]
+    async fn line_protocol_escapes_tag_values() {
+        let health = HealthCheckBuilder::new("my service", "1.0.0")
+            .add_check("db,primary=1", mock_check(CheckResult::healthy()))
+            .build();
+
+        let lines = health.line_protocol().await;
+
+        assert!(
+            lines.starts_with("health_check,service=my\\ service,check=db\\,primary\\=1,"),
+            "{}",
+            lines
+        );
+    }
 }
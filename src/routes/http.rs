@@ -0,0 +1,385 @@
+//! Axum routes and handlers for the health endpoints
+
+use super::HealthRoutes;
+use crate::builder::HealthCheckBuilder;
+use crate::checks::HealthCheck;
+use crate::layer::HealthStatusLayer;
+use crate::response::CheckStatus;
+#[cfg(feature = "openapi")]
+use crate::response::HealthResponse;
+use axum::{
+    body::Bytes,
+    extract::{ConnectInfo, Query, Request},
+    http::{header, HeaderMap, Method, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post, Route},
+    Json, Router,
+};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tower::{Layer, Service};
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Plain-text readiness bodies for load balancers that match content
+#[derive(Debug)]
+pub(crate) struct PlainTextBodies {
+    pub(crate) ready: String,
+    pub(crate) not_ready: String,
+}
+
+impl HealthRoutes {
+    /// Create Axum routes for health endpoints
+    ///
+    /// Adds:
+    /// - `GET /health` - Liveness probe (200 unless a liveness check is unhealthy)
+    /// - `GET /ready` - Readiness probe (200 if healthy or degraded, 503 if not)
+    /// - `GET /health/stream` - Server-Sent Events of readiness status changes
+    pub fn routes(&self) -> Router {
+        let health_handler = self.clone();
+        let ready_handler = self.clone();
+        let stream_handler = self.clone();
+
+        let router = Router::new()
+            .route("/health", get(move || health_endpoint(health_handler)))
+            .route("/ready", get(move |request: Request| readiness_endpoint(ready_handler, request)))
+            .route("/health/stream", get(move || stream_endpoint(stream_handler)));
+
+        let router = match &self.maintenance_token {
+            Some(token) => {
+                let maintenance_handler = self.clone();
+                let token = token.clone();
+                router.route(
+                    "/maintenance",
+                    post(move |headers: HeaderMap, body: Bytes| {
+                        maintenance_endpoint(maintenance_handler, token, headers, body)
+                    }),
+                )
+            }
+            None => router,
+        };
+
+        let router = match self.compression_threshold {
+            Some(min_bytes) => router.layer(CompressionLayer::new().compress_when(
+                SizeAbove::new(min_bytes).and(NotForContentType::SSE),
+            )),
+            None => router,
+        };
+
+        match &self.cors_origins {
+            Some(origins) => router.layer(
+                CorsLayer::new()
+                    .allow_origin(AllowOrigin::list(origins.iter().cloned()))
+                    .allow_methods([Method::GET, Method::OPTIONS]),
+            ),
+            None => router,
+        }
+    }
+
+    /// Create Axum routes for health endpoints under `prefix`
+    ///
+    /// `routes_nested("/svc/orders")` serves `/svc/orders/health`,
+    /// `/svc/orders/ready` and so on. Leading and trailing slashes are
+    /// normalized, and an empty prefix (or `/`) behaves like
+    /// [`routes`](Self::routes).
+    pub fn routes_nested(&self, prefix: &str) -> Router {
+        let prefix = prefix.trim_matches('/');
+
+        if prefix.is_empty() {
+            self.routes()
+        } else {
+            Router::new().nest(&format!("/{}", prefix), self.routes())
+        }
+    }
+
+    /// Create Axum routes for health endpoints to merge into a stateful router
+    ///
+    /// Same endpoints as [`routes`](Self::routes), typed as `Router<S>` so
+    /// they can be merged before the application calls `with_state`. Checks
+    /// needing that state should use
+    /// [`state_check`](crate::checks::state_check).
+    pub fn routes_with_state<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        self.routes().with_state(())
+    }
+
+    /// Create Axum routes for health endpoints behind caller-supplied middleware
+    ///
+    /// `layer` wraps only the health routes, so request-id propagation, rate
+    /// limiting (e.g. to protect dependencies from a probe storm on `/ready`)
+    /// or logging can be applied without affecting the rest of the app. Use
+    /// `tower::ServiceBuilder` to combine several layers.
+    pub fn routes_with_layer<L>(&self, layer: L) -> Router
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.routes().layer(layer)
+    }
+
+    /// Layer that adds `X-Health-Status` to every application response
+    ///
+    /// Reports the status cached by the last readiness evaluation without
+    /// running checks. See [`HealthStatusLayer`].
+    pub fn status_layer(&self) -> HealthStatusLayer {
+        HealthStatusLayer::new(self.latest.clone())
+    }
+}
+
+/// Health endpoint handler (liveness probe)
+///
+/// Returns 200 OK with basic service info, or 503 Service Unavailable if a
+/// liveness check is unhealthy
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Service is alive", body = HealthResponse),
+        (status = 503, description = "A liveness check failed", body = HealthResponse),
+    ),
+))]
+pub(crate) async fn health_endpoint(routes: HealthRoutes) -> Response {
+    let response = routes.evaluate_liveness().await;
+
+    let status_code = if response.status == CheckStatus::Unhealthy {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    (status_code, Json(response)).into_response()
+}
+
+/// Readiness endpoint handler (readiness probe)
+///
+/// Returns 200 OK if all critical checks pass, 503 Service Unavailable
+/// otherwise. A degraded service (e.g. only optional checks failing) stays in
+/// rotation with a 200 and `"status": "degraded"` in the body.
+///
+/// The `detail` query parameter controls the body: `full` (default) includes
+/// every check, `summary` only the aggregate status and counts, and `none`
+/// an empty body for probes that only read the status code. Routes built
+/// with plain-text readiness return the configured token instead.
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    params(
+        ("detail" = Option<String>, Query, description = "Body detail level: full, summary or none"),
+    ),
+    responses(
+        (status = 200, description = "Service is ready or degraded", body = HealthResponse),
+        (status = 503, description = "Service is not ready", body = HealthResponse),
+    ),
+))]
+pub(crate) async fn readiness_endpoint(routes: HealthRoutes, request: Request) -> Response {
+    let response = routes.evaluate().await;
+
+    if let Some(logger) = &routes.probe_logger {
+        let remote_addr = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+        logger.log(&response, remote_addr);
+    }
+
+    // Return appropriate status code
+    let status_code = match routes.mixed_status {
+        Some(mixed_status) if response.is_mixed() => mixed_status,
+        _ => response.status_code(),
+    };
+
+    if let Some(bodies) = &routes.plain_text {
+        let body = if response.is_ready() {
+            bodies.ready.clone()
+        } else {
+            bodies.not_ready.clone()
+        };
+        return (status_code, body).into_response();
+    }
+
+    let detail = Query::<ReadinessQuery>::try_from_uri(request.uri())
+        .map(|Query(query)| query.detail)
+        .unwrap_or_default();
+
+    match detail {
+        DetailLevel::Full => (status_code, Json(response)).into_response(),
+        DetailLevel::Summary => {
+            let mut response = response.with_summary();
+            response.checks.clear();
+            (status_code, Json(response)).into_response()
+        }
+        DetailLevel::None => status_code.into_response(),
+    }
+}
+
+/// Status stream handler
+///
+/// Sends the latest readiness result on connect, then a new event each time
+/// the aggregate status changes. Subscribers that fall behind skip straight
+/// to the newest update instead of holding back the sender.
+async fn stream_endpoint(
+    routes: HealthRoutes,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    if routes.latest_response().is_none() {
+        routes.evaluate().await;
+    }
+
+    let receiver = routes.updates.subscribe();
+    let snapshot = routes.latest_response();
+
+    let updates = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(response) => return Some((response, receiver)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let events = futures::stream::iter(snapshot)
+        .chain(updates)
+        .map(|response| Event::default().json_data(response));
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Query parameters accepted by `GET /ready`
+#[derive(Debug, Deserialize)]
+struct ReadinessQuery {
+    #[serde(default)]
+    detail: DetailLevel,
+}
+
+/// How much of the readiness response to serialize
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DetailLevel {
+    #[default]
+    Full,
+    Summary,
+    None,
+}
+
+/// Body of a `POST /maintenance` request
+#[derive(Debug, Deserialize)]
+struct MaintenanceRequest {
+    enabled: bool,
+}
+
+/// Body of a `POST /maintenance` response
+#[derive(Debug, Serialize)]
+struct MaintenanceResponse {
+    maintenance: bool,
+}
+
+/// Maintenance toggle handler
+///
+/// Requires `Authorization: Bearer <token>`; returns 401 otherwise, before
+/// the body is parsed
+async fn maintenance_endpoint(
+    routes: HealthRoutes,
+    token: Arc<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()));
+
+    if !authorized {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let request: MaintenanceRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, format!("Invalid maintenance request: {}", e))
+                .into_response()
+        }
+    };
+
+    routes.set_maintenance(request.enabled);
+    tracing::info!(enabled = request.enabled, "maintenance mode changed");
+
+    Json(MaintenanceResponse {
+        maintenance: request.enabled,
+    })
+    .into_response()
+}
+
+/// Compare secrets without short-circuiting on the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Standalone health check routes (simpler API)
+///
+/// Create health routes directly without builder
+pub fn health_routes(
+    service_name: impl Into<String>,
+    version: Option<String>,
+    checks: HashMap<String, HealthCheck>,
+) -> Router {
+    let builder = match version {
+        Some(version) => HealthCheckBuilder::new(service_name, version),
+        None => HealthCheckBuilder::without_version(service_name),
+    };
+
+    checks
+        .into_iter()
+        .fold(builder, |builder, (name, check)| builder.add_check(name, check))
+        .build()
+        .routes()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::HealthCheckBuilder;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn post_maintenance(token: Option<&str>, body: &'static str) -> StatusCode {
+        let app = HealthCheckBuilder::new("test", "1.0.0")
+            .with_maintenance_endpoint("secret")
+            .build()
+            .routes();
+
+        let mut request = Request::post("/maintenance");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        app.oneshot(request.body(Body::from(body)).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn maintenance_checks_the_token_before_the_body() {
+        assert_eq!(post_maintenance(None, "garbage").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post_maintenance(Some("wrong"), "garbage").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post_maintenance(Some("secret"), "garbage").await, StatusCode::BAD_REQUEST);
+        assert_eq!(post_maintenance(Some("secret"), r#"{"enabled":true}"#).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,109 @@
+//! Test helpers for exercising health wiring without live dependencies
+//!
+//! Enabled with the `testing` feature.
+
+use crate::builder::HealthCheckBuilder;
+use crate::checks::HealthCheck;
+use crate::clock::Clock;
+use crate::response::CheckResult;
+use crate::routes::HealthRoutes;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Create a health check that always returns `result`
+pub fn mock_check(result: CheckResult) -> HealthCheck {
+    Box::new(move || {
+        let result = result.clone();
+        Box::pin(async move { result })
+    })
+}
+
+/// Create a health check that alternates healthy and unhealthy
+///
+/// The first call is healthy, the second unhealthy, and so on.
+pub fn flapping_check() -> HealthCheck {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    Box::new(move || {
+        let call = calls.fetch_add(1, Ordering::Relaxed);
+        Box::pin(async move {
+            if call.is_multiple_of(2) {
+                CheckResult::healthy()
+            } else {
+                CheckResult::unhealthy("flapping check failed")
+            }
+        })
+    })
+}
+
+/// Build health routes whose checks return fixed results
+///
+/// Useful for driving the Axum router in integration tests:
+///
+/// ```rust
+/// use pleme_health::response::CheckResult;
+/// use pleme_health::testing::mock_routes;
+///
+/// let health = mock_routes("my-service", [
+///     ("database", CheckResult::healthy()),
+///     ("cache", CheckResult::unhealthy("connection refused")),
+/// ]);
+/// # #[cfg(feature = "axum")]
+/// let app = health.routes();
+/// ```
+pub fn mock_routes<I, S>(service_name: impl Into<String>, results: I) -> HealthRoutes
+where
+    I: IntoIterator<Item = (S, CheckResult)>,
+    S: Into<String>,
+{
+    results
+        .into_iter()
+        .fold(
+            HealthCheckBuilder::without_version(service_name),
+            |builder, (name, result)| builder.add_check(name, mock_check(result)),
+        )
+        .build()
+}
+
+/// Clock that only moves when advanced manually
+///
+/// Both wall-clock and monotonic time start at fixed points and advance
+/// together, so timestamps and durations can be asserted exactly.
+#[derive(Debug)]
+pub struct MockClock {
+    start_time: DateTime<Utc>,
+    start_instant: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// Create a clock frozen at `start_time`
+    pub fn new(start_time: DateTime<Utc>) -> Self {
+        Self {
+            start_time,
+            start_instant: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.start_time + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+}